@@ -0,0 +1,263 @@
+use std::cmp::{max, min};
+use std::fmt::{Debug, Display, Formatter};
+
+/// Integer types usable as interval bounds.
+pub trait Integer: Copy + Ord + Debug {
+    fn succ(self) -> Self;
+    fn pred(self) -> Self;
+    /// Number of integers in `lo..=hi`, assuming `lo <= hi`.
+    fn count(lo: Self, hi: Self) -> u64;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                fn succ(self) -> Self {
+                    self + 1
+                }
+
+                fn pred(self) -> Self {
+                    self - 1
+                }
+
+                fn count(lo: Self, hi: Self) -> u64 {
+                    (hi as i128 - lo as i128 + 1) as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, u8, u16, u32, u64, usize, isize);
+
+/// Inclusive interval `[start, end]` of integers.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+// An inclusive interval always contains at least one integer.
+#[allow(clippy::len_without_is_empty)]
+impl<T: Integer> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
+        assert!(start <= end, "Bad interval: {:?} > {:?}", start, end);
+        Self { start, end }
+    }
+
+    /// Number of integers in the interval.
+    pub fn len(&self) -> u64 {
+        T::count(self.start, self.end)
+    }
+
+    pub fn contains(&self, x: T) -> bool {
+        self.start <= x && x <= self.end
+    }
+
+    /// Returns `true` if `other` lies completely inside `self`.
+    pub fn contains_interval(&self, other: &Self) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        self.overlaps(other)
+            .then(|| Self::new(max(self.start, other.start), min(self.end, other.end)))
+    }
+}
+
+impl<T: Display> Display for Interval<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// Union of disjoint intervals, kept sorted and with touching intervals merged.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T: Integer> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Integer> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+
+    /// Builds a normalized set from arbitrary (possibly overlapping) intervals.
+    fn normalize(mut intervals: Vec<Interval<T>>) -> Self {
+        intervals.sort_unstable_by_key(|i| i.start);
+        let mut result: Vec<Interval<T>> = Vec::with_capacity(intervals.len());
+        for interval in intervals {
+            match result.last_mut() {
+                Some(last) if interval.start <= last.end || interval.start == last.end.succ() => {
+                    last.end = max(last.end, interval.end);
+                }
+                _ => result.push(interval),
+            }
+        }
+        Self { intervals: result }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Total number of integers in the set.
+    pub fn len(&self) -> u64 {
+        self.intervals.iter().map(|i| i.len()).sum()
+    }
+
+    pub fn contains(&self, x: T) -> bool {
+        let i = self.intervals.partition_point(|i| i.end < x);
+        i < self.intervals.len() && self.intervals[i].contains(x)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval<T>> {
+        self.intervals.iter()
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        // Range of intervals overlapping or touching the new one.
+        let lo = self
+            .intervals
+            .partition_point(|i| i.end < interval.start && i.end.succ() < interval.start);
+        let hi = self
+            .intervals
+            .partition_point(|i| i.start <= interval.end || i.start.pred() <= interval.end);
+        let mut merged = interval;
+        if lo < hi {
+            merged.start = min(merged.start, self.intervals[lo].start);
+            merged.end = max(merged.end, self.intervals[hi - 1].end);
+        }
+        self.intervals.splice(lo..hi, std::iter::once(merged));
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::normalize(self.iter().chain(other.iter()).copied().collect())
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut result = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let a = &self.intervals[i];
+            let b = &other.intervals[j];
+            if let Some(c) = a.intersection(b) {
+                result.push(c);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { intervals: result }
+    }
+
+    /// Integers of `bounds` not covered by the set.
+    pub fn complement(&self, bounds: Interval<T>) -> Self {
+        let mut result = Vec::new();
+        let mut start = bounds.start;
+        let first = self.intervals.partition_point(|i| i.end < bounds.start);
+        for interval in &self.intervals[first..] {
+            if interval.end < start {
+                continue;
+            }
+            if interval.start > bounds.end {
+                break;
+            }
+            if interval.start > start {
+                result.push(Interval::new(start, interval.start.pred()));
+            }
+            if interval.end >= bounds.end {
+                return Self { intervals: result };
+            }
+            start = interval.end.succ();
+        }
+        result.push(Interval::new(start, bounds.end));
+        Self { intervals: result }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        match (self.intervals.first(), self.intervals.last()) {
+            (Some(first), Some(last)) => {
+                let bounds = Interval::new(first.start, last.end);
+                self.intersection(&other.complement(bounds))
+            }
+            _ => Self::new(),
+        }
+    }
+}
+
+impl<T: Integer> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        Self::normalize(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(intervals: &[(i32, i32)]) -> IntervalSet<i32> {
+        intervals
+            .iter()
+            .map(|&(a, b)| Interval::new(a, b))
+            .collect()
+    }
+
+    #[test]
+    fn interval_relations() {
+        let a = Interval::new(2, 8);
+        let b = Interval::new(3, 7);
+        let c = Interval::new(8, 9);
+        assert!(a.contains_interval(&b));
+        assert!(!b.contains_interval(&a));
+        assert!(a.overlaps(&c));
+        assert!(!b.overlaps(&c));
+        assert_eq!(a.intersection(&c), Some(Interval::new(8, 8)));
+        assert_eq!(b.intersection(&c), None);
+        assert_eq!(a.len(), 7);
+    }
+
+    #[test]
+    fn set_normalization() {
+        let s = set(&[(5, 7), (1, 3), (4, 4), (10, 12), (11, 15)]);
+        assert_eq!(s, set(&[(1, 7), (10, 15)]));
+        assert_eq!(s.len(), 13);
+        let mut t = s.clone();
+        t.insert(Interval::new(8, 9));
+        assert_eq!(t, set(&[(1, 15)]));
+        t.insert(Interval::new(20, 20));
+        t.insert(Interval::new(-5, -3));
+        assert_eq!(t, set(&[(-5, -3), (1, 15), (20, 20)]));
+        assert!(s.contains(4));
+        assert!(!s.contains(8));
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = set(&[(1, 5), (10, 20)]);
+        let b = set(&[(4, 12)]);
+        assert_eq!(a.union(&b), set(&[(1, 20)]));
+        assert_eq!(a.intersection(&b), set(&[(4, 5), (10, 12)]));
+        assert_eq!(a.difference(&b), set(&[(1, 3), (13, 20)]));
+        assert_eq!(
+            a.complement(Interval::new(0, 25)),
+            set(&[(0, 0), (6, 9), (21, 25)])
+        );
+        assert_eq!(a.complement(Interval::new(2, 4)), IntervalSet::new());
+    }
+}
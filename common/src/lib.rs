@@ -0,0 +1,4 @@
+//! Utilities shared between the days.
+
+pub mod interval;
+pub mod rng;
@@ -0,0 +1,37 @@
+/// Xorshift64 generator: tiny and deterministic, good enough for producing test inputs.
+#[derive(Debug, Clone)]
+pub struct XorShift(u64);
+
+impl XorShift {
+    /// A zero state would only ever produce zeros, so the seed 0 is replaced by 1.
+    pub fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Number in `0..n`, with a negligible bias for small `n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deterministic() {
+        let a = (0..100).scan(XorShift::new(42), |rng, _| Some(rng.below(10)));
+        let b = (0..100).scan(XorShift::new(42), |rng, _| Some(rng.below(10)));
+        assert!(a.eq(b));
+        let mut rng = XorShift::new(0);
+        assert!((0..1000).all(|_| rng.next_u64() != 0));
+        assert!((0..1000).all(|_| rng.below(7) < 7));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{play, Item};
+
+pub const ITEMS: [Item; 3] = [Item::Rock, Item::Paper, Item::Scissors];
+
+/// Score the response player gets for answering `answer` to `opponent`.
+pub fn payoff(opponent: Item, answer: Item) -> u32 {
+    answer.score() + play(opponent, answer).score()
+}
+
+/// Payoff matrix indexed as `[answer][opponent]`.
+fn payoff_matrix() -> [[f64; 3]; 3] {
+    let mut m = [[0.0; 3]; 3];
+    for (i, &answer) in ITEMS.iter().enumerate() {
+        for (j, &opponent) in ITEMS.iter().enumerate() {
+            m[i][j] = payoff(opponent, answer) as f64;
+        }
+    }
+    m
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Strategy {
+    /// Probabilities of playing Rock, Paper and Scissors.
+    pub probabilities: [f64; 3],
+    /// Expected score per round.
+    pub value: f64,
+}
+
+/// Solves the square linear system `a * x = b` using Gaussian elimination.
+/// Returns `None` if the system is singular.
+fn solve_linear(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&x, &y| a[x][col].abs().total_cmp(&a[y][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = a[col].clone();
+        for row in 0..n {
+            if row != col {
+                let factor = a[row][col] / pivot_row[col];
+                for (x, p) in a[row].iter_mut().zip(&pivot_row).skip(col) {
+                    *x -= factor * p;
+                }
+                b[row] -= factor * b[col];
+            }
+        }
+    }
+    Some((0..n).map(|i| b[i] / a[i][i]).collect())
+}
+
+/// Computes the maximin mixed strategy of the response player, i.e. the strategy
+/// maximizing the expected score guaranteed against any opponent strategy.
+///
+/// The optimum of the underlying linear program lies at a vertex, so it is enough
+/// to enumerate the supports `S` (our moves) and `T` (opponent moves) of equal size,
+/// solve the system making all moves from `T` equally good for the opponent,
+/// and keep the best feasible solution.
+pub fn equilibrium() -> Strategy {
+    let m = payoff_matrix();
+    let mut best: Option<Strategy> = None;
+
+    for s in 1u8..8 {
+        for t in 1u8..8 {
+            if s.count_ones() != t.count_ones() {
+                continue;
+            }
+            let rows = (0..3).filter(|i| s & (1 << i) != 0).collect::<Vec<_>>();
+            let cols = (0..3).filter(|j| t & (1 << j) != 0).collect::<Vec<_>>();
+            let k = rows.len();
+
+            // Unknowns: p_i for i in rows, then v.
+            let mut a = Vec::new();
+            let mut b = Vec::new();
+            for &j in &cols {
+                let mut eq = rows.iter().map(|&i| m[i][j]).collect::<Vec<_>>();
+                eq.push(-1.0);
+                a.push(eq);
+                b.push(0.0);
+            }
+            let mut eq = vec![1.0; k];
+            eq.push(0.0);
+            a.push(eq);
+            b.push(1.0);
+
+            let Some(x) = solve_linear(a, b) else {
+                continue;
+            };
+            if x[..k].iter().any(|&p| p < -1e-9) {
+                continue;
+            }
+            let mut probabilities = [0.0; 3];
+            for (&i, &p) in rows.iter().zip(&x) {
+                probabilities[i] = p.max(0.0);
+            }
+            let value = (0..3)
+                .map(|j| (0..3).map(|i| probabilities[i] * m[i][j]).sum::<f64>())
+                .fold(f64::INFINITY, f64::min);
+            if best.is_none_or(|b| value > b.value + 1e-9) {
+                best = Some(Strategy {
+                    probabilities,
+                    value,
+                });
+            }
+        }
+    }
+
+    best.expect("Matrix game always has a solution")
+}
+
+/// Computes the pure best response against the given opponent distribution
+/// (probabilities of Rock, Paper and Scissors), together with its expected score.
+pub fn best_response(opponent: [f64; 3]) -> (Item, f64) {
+    let m = payoff_matrix();
+    ITEMS
+        .iter()
+        .enumerate()
+        .map(|(i, &item)| (item, (0..3).map(|j| opponent[j] * m[i][j]).sum::<f64>()))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equilibrium_is_unexploitable() {
+        let strategy = equilibrium();
+        let total: f64 = strategy.probabilities.iter().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        for opponent in ITEMS {
+            let expected: f64 = ITEMS
+                .iter()
+                .zip(strategy.probabilities)
+                .map(|(&answer, p)| p * payoff(opponent, answer) as f64)
+                .sum();
+            assert!(expected >= strategy.value - 1e-9);
+        }
+    }
+
+    #[test]
+    fn best_response_to_pure_rock_is_paper() {
+        let (item, value) = best_response([1.0, 0.0, 0.0]);
+        assert_eq!(item, Item::Paper);
+        assert_eq!(value, 8.0);
+    }
+}
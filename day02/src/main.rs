@@ -5,6 +5,12 @@ use std::path::PathBuf;
 use clap::Parser;
 use itertools::Itertools;
 
+use crate::game::{best_response, equilibrium, ITEMS};
+use crate::report::{print_report, write_rounds_csv, write_stats_csv, Round};
+
+mod game;
+mod report;
+
 #[derive(Parser, Debug)]
 #[command(author, version)]
 struct Cli {
@@ -12,9 +18,21 @@ struct Cli {
     #[arg(value_name = "FILE")]
     #[arg(default_value = "data/sample.txt")]
     input: PathBuf,
+
+    /// Print the per-round breakdown and aggregate statistics
+    #[arg(long)]
+    report: bool,
+
+    /// Export the per-round breakdown as CSV
+    #[arg(long, value_name = "FILE")]
+    csv: Option<PathBuf>,
+
+    /// Export the aggregate statistics as CSV
+    #[arg(long, value_name = "FILE")]
+    stats_csv: Option<PathBuf>,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 enum Item {
     Rock,
     Paper,
@@ -48,6 +66,49 @@ impl Outcome {
     }
 }
 
+fn parse_opponent(c: char) -> Item {
+    match c {
+        'A' => Item::Rock,
+        'B' => Item::Paper,
+        'C' => Item::Scissors,
+        _ => panic!("Bad opponent move {:?}", c),
+    }
+}
+
+/// Part one: the second column is the move to play.
+fn answer_part_one(b: char) -> Item {
+    match b {
+        'X' => Item::Rock,
+        'Y' => Item::Paper,
+        'Z' => Item::Scissors,
+        _ => panic!("Bad answer move {:?}", b),
+    }
+}
+
+/// Part two: the second column is the desired outcome.
+fn answer_part_two(opponent: Item, b: char) -> Item {
+    match b {
+        // Need to lose:
+        'X' => match opponent {
+            Item::Rock => Item::Scissors,
+            Item::Paper => Item::Rock,
+            Item::Scissors => Item::Paper,
+        },
+
+        // Need to draw:
+        'Y' => opponent,
+
+        // Need to win:
+        'Z' => match opponent {
+            Item::Rock => Item::Paper,
+            Item::Paper => Item::Scissors,
+            Item::Scissors => Item::Rock,
+        },
+
+        _ => panic!("Bad answer move {:?}", b),
+    }
+}
+
 fn play(a: Item, b: Item) -> Outcome {
     use Item::*;
     use Outcome::*;
@@ -73,7 +134,7 @@ fn main() -> color_eyre::Result<()> {
     let path = args.input;
     let lines = BufReader::new(File::open(path)?)
         .lines()
-        .filter_map(|x| x.ok());
+        .map_while(Result::ok);
 
     let data = lines
         .map(|line| {
@@ -86,61 +147,76 @@ fn main() -> color_eyre::Result<()> {
         })
         .collect_vec();
 
-    println!("==> Solving part one...");
-    let score1: u32 = data
+    let rounds1 = data
+        .iter()
+        .map(|&(a, b)| {
+            let opponent = parse_opponent(a);
+            Round::new(opponent, answer_part_one(b))
+        })
+        .collect_vec();
+    let rounds2 = data
         .iter()
-        .map(|(a, b)| {
-            let opponent = match a {
-                'A' => Item::Rock,
-                'B' => Item::Paper,
-                'C' => Item::Scissors,
-                _ => panic!("Bad opponent move {:?}", a),
-            };
-            let answer = match b {
-                'X' => Item::Rock,
-                'Y' => Item::Paper,
-                'Z' => Item::Scissors,
-                _ => panic!("Bad answer move {:?}", b),
-            };
-            answer.score() + play(opponent, answer).score()
+        .map(|&(a, b)| {
+            let opponent = parse_opponent(a);
+            Round::new(opponent, answer_part_two(opponent, b))
         })
-        .sum();
+        .collect_vec();
+
+    println!("==> Solving part one...");
+    let score1: u32 = rounds1.iter().map(|r| r.score).sum();
     println!("Score: {}", score1);
 
     println!("==> Solving part two...");
-    let score2: u32 = data
-        .iter()
-        .map(|(a, b)| {
-            let opponent = match a {
-                'A' => Item::Rock,
-                'B' => Item::Paper,
-                'C' => Item::Scissors,
-                _ => panic!("Bad opponent move {:?}", a),
-            };
-            let answer = match b {
-                // Need to lose:
-                'X' => match opponent {
-                    Item::Rock => Item::Scissors,
-                    Item::Paper => Item::Rock,
-                    Item::Scissors => Item::Paper,
-                },
-
-                // Need to draw:
-                'Y' => opponent,
-
-                // Need to win:
-                'Z' => match opponent {
-                    Item::Rock => Item::Paper,
-                    Item::Paper => Item::Scissors,
-                    Item::Scissors => Item::Rock,
-                },
-
-                _ => panic!("Bad answer move {:?}", b),
-            };
-            answer.score() + play(opponent, answer).score()
-        })
-        .sum();
+    let score2: u32 = rounds2.iter().map(|r| r.score).sum();
     println!("Score: {}", score2);
 
+    if args.report {
+        print_report(&rounds1, &rounds2);
+    }
+    if let Some(path) = &args.csv {
+        write_rounds_csv(File::create(path)?, &rounds1, &rounds2)?;
+        println!("Rounds written to {}", path.display());
+    }
+    if let Some(path) = &args.stats_csv {
+        write_stats_csv(File::create(path)?, &rounds1, &rounds2)?;
+        println!("Statistics written to {}", path.display());
+    }
+
+    println!("==> Game-theoretic analysis...");
+    if data.is_empty() {
+        // Per-round figures would all be 0/0.
+        println!("No rounds to analyze");
+        return Ok(());
+    }
+    let rounds = data.len() as f64;
+    let counts = data.iter().map(|(a, _)| parse_opponent(*a)).counts();
+    let empirical = ITEMS.map(|item| *counts.get(&item).unwrap_or(&0) as f64 / rounds);
+    println!(
+        "Opponent distribution: Rock = {:.3}, Paper = {:.3}, Scissors = {:.3}",
+        empirical[0], empirical[1], empirical[2]
+    );
+    let strategy = equilibrium();
+    println!(
+        "Equilibrium strategy: Rock = {:.3}, Paper = {:.3}, Scissors = {:.3}",
+        strategy.probabilities[0], strategy.probabilities[1], strategy.probabilities[2]
+    );
+    println!(
+        "Equilibrium: {:.3} per round, {:.1} in total",
+        strategy.value,
+        strategy.value * rounds
+    );
+    let (item, value) = best_response(empirical);
+    println!(
+        "Best response ({:?}): {:.3} per round, {:.1} in total",
+        item,
+        value,
+        value * rounds
+    );
+    println!(
+        "Guide: part one {:.3} per round, part two {:.3} per round",
+        score1 as f64 / rounds,
+        score2 as f64 / rounds
+    );
+
     Ok(())
 }
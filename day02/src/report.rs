@@ -0,0 +1,194 @@
+use std::io::Write;
+
+use crate::game::{payoff, ITEMS};
+use crate::{play, Item, Outcome};
+
+#[derive(Debug, Copy, Clone)]
+pub struct Round {
+    pub opponent: Item,
+    pub answer: Item,
+    pub outcome: Outcome,
+    pub score: u32,
+}
+
+impl Round {
+    pub fn new(opponent: Item, answer: Item) -> Self {
+        Self {
+            opponent,
+            answer,
+            outcome: play(opponent, answer),
+            score: payoff(opponent, answer),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    /// Total score obtained with each of our moves (Rock, Paper, Scissors).
+    pub score_by_move: [u32; 3],
+    pub longest_win_streak: usize,
+    pub longest_loss_streak: usize,
+}
+
+impl Stats {
+    pub fn new(rounds: &[Round]) -> Self {
+        let mut stats = Stats::default();
+        let mut win_streak = 0;
+        let mut loss_streak = 0;
+        for round in rounds {
+            match round.outcome {
+                Outcome::Win => stats.wins += 1,
+                Outcome::Draw => stats.draws += 1,
+                Outcome::Loss => stats.losses += 1,
+            }
+            let i = ITEMS.iter().position(|&x| x == round.answer).unwrap();
+            stats.score_by_move[i] += round.score;
+
+            win_streak = if round.outcome == Outcome::Win {
+                win_streak + 1
+            } else {
+                0
+            };
+            loss_streak = if round.outcome == Outcome::Loss {
+                loss_streak + 1
+            } else {
+                0
+            };
+            stats.longest_win_streak = stats.longest_win_streak.max(win_streak);
+            stats.longest_loss_streak = stats.longest_loss_streak.max(loss_streak);
+        }
+        stats
+    }
+}
+
+pub fn print_report(rounds1: &[Round], rounds2: &[Round]) {
+    println!("==> Per-round breakdown...");
+    println!(
+        "{:>6} | {:<8} | {:<8} {:<4} {:>5} | {:<8} {:<4} {:>5}",
+        "round", "opponent", "answer1", "out1", "score", "answer2", "out2", "score"
+    );
+    for (i, (r1, r2)) in rounds1.iter().zip(rounds2).enumerate() {
+        println!(
+            "{:>6} | {:<8} | {:<8} {:<4} {:>5} | {:<8} {:<4} {:>5}",
+            i + 1,
+            format!("{:?}", r1.opponent),
+            format!("{:?}", r1.answer),
+            format!("{:?}", r1.outcome),
+            r1.score,
+            format!("{:?}", r2.answer),
+            format!("{:?}", r2.outcome),
+            r2.score,
+        );
+    }
+
+    println!("==> Statistics...");
+    for (name, rounds) in [("Part one", rounds1), ("Part two", rounds2)] {
+        let stats = Stats::new(rounds);
+        println!("{}:", name);
+        println!(
+            "  wins = {}, draws = {}, losses = {}",
+            stats.wins, stats.draws, stats.losses
+        );
+        for (item, score) in ITEMS.iter().zip(stats.score_by_move) {
+            println!("  score with {:?} = {}", item, score);
+        }
+        println!(
+            "  longest winning streak = {}, longest losing streak = {}",
+            stats.longest_win_streak, stats.longest_loss_streak
+        );
+    }
+}
+
+pub fn write_rounds_csv(
+    mut w: impl Write,
+    rounds1: &[Round],
+    rounds2: &[Round],
+) -> std::io::Result<()> {
+    writeln!(
+        w,
+        "round,opponent,answer1,outcome1,score1,answer2,outcome2,score2"
+    )?;
+    for (i, (r1, r2)) in rounds1.iter().zip(rounds2).enumerate() {
+        writeln!(
+            w,
+            "{},{:?},{:?},{:?},{},{:?},{:?},{}",
+            i + 1,
+            r1.opponent,
+            r1.answer,
+            r1.outcome,
+            r1.score,
+            r2.answer,
+            r2.outcome,
+            r2.score
+        )?;
+    }
+    Ok(())
+}
+
+pub fn write_stats_csv(
+    mut w: impl Write,
+    rounds1: &[Round],
+    rounds2: &[Round],
+) -> std::io::Result<()> {
+    writeln!(
+        w,
+        "part,wins,draws,losses,score_rock,score_paper,score_scissors,longest_win_streak,longest_loss_streak"
+    )?;
+    for (part, rounds) in [(1, rounds1), (2, rounds2)] {
+        let stats = Stats::new(rounds);
+        writeln!(
+            w,
+            "{},{},{},{},{},{},{},{},{}",
+            part,
+            stats.wins,
+            stats.draws,
+            stats.losses,
+            stats.score_by_move[0],
+            stats.score_by_move[1],
+            stats.score_by_move[2],
+            stats.longest_win_streak,
+            stats.longest_loss_streak
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        use Item::*;
+        let rounds = [
+            (Rock, Paper),
+            (Scissors, Rock),
+            (Paper, Scissors),
+            (Rock, Rock),
+            (Paper, Rock),
+            (Scissors, Paper),
+            (Paper, Paper),
+            (Scissors, Rock),
+        ]
+        .map(|(opponent, answer)| Round::new(opponent, answer));
+        let scores = rounds.map(|r| r.score);
+        assert_eq!(scores, [8, 7, 9, 4, 1, 2, 5, 7]);
+
+        let stats = Stats::new(&rounds);
+        assert_eq!((stats.wins, stats.draws, stats.losses), (4, 2, 2));
+        assert_eq!(stats.score_by_move, [7 + 4 + 1 + 7, 8 + 2 + 5, 9]);
+        assert_eq!(stats.longest_win_streak, 3);
+        assert_eq!(stats.longest_loss_streak, 2);
+
+        let stats = Stats::new(&[]);
+        assert_eq!((stats.wins, stats.draws, stats.losses), (0, 0, 0));
+        assert_eq!(stats.score_by_move, [0; 3]);
+        assert_eq!(
+            (stats.longest_win_streak, stats.longest_loss_streak),
+            (0, 0)
+        );
+    }
+}
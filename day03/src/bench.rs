@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+use std::time::Instant;
+
+use common::rng::XorShift;
+use itertools::Itertools;
+
+use crate::priority::{char_to_priority, PriorityTable};
+use crate::{solve_part_one, solve_part_two};
+
+const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Generates `groups` groups of three valid rucksacks: each rucksack has exactly
+/// one item shared between its compartments, and each group has exactly one badge.
+pub fn generate(groups: usize, seed: u64) -> Vec<String> {
+    let mut rng = XorShift::new(seed);
+    let mut lines = Vec::with_capacity(3 * groups);
+    for _ in 0..groups {
+        let mut letters = ALPHABET.to_vec();
+        for i in (1..letters.len()).rev() {
+            letters.swap(i, rng.below(i + 1));
+        }
+        let badge = letters[0];
+        // Three disjoint pools of 17 letters, one per rucksack.
+        for pool in letters[1..].chunks(17) {
+            let shared = pool[0];
+            let left_pool = &pool[1..9];
+            let right_pool = &pool[9..];
+            let half = 8 + rng.below(24);
+            let mut left = (0..half)
+                .map(|_| left_pool[rng.below(left_pool.len())])
+                .collect_vec();
+            let mut right = (0..half)
+                .map(|_| right_pool[rng.below(right_pool.len())])
+                .collect_vec();
+            left[rng.below(half)] = shared;
+            right[rng.below(half)] = shared;
+            let mut i = rng.below(half);
+            while left[i] == shared {
+                i = (i + 1) % half;
+            }
+            left[i] = badge;
+            let line = left.into_iter().chain(right).map(char::from).collect();
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+fn intersection(mut sets: Vec<HashSet<char>>) -> HashSet<char> {
+    if sets.is_empty() {
+        return HashSet::new();
+    }
+
+    if sets.len() == 1 {
+        return sets.pop().unwrap();
+    }
+
+    let mut result = sets.pop().unwrap();
+    result.retain(|item| sets.iter().all(|set| set.contains(item)));
+    result
+}
+
+/// Reference `HashSet`-based implementation of part one.
+fn solve_part_one_hashset(data: &[String]) -> u32 {
+    let mut total_priority = 0;
+    for line in data {
+        let line = line.chars().collect_vec();
+        assert_eq!(line.len() % 2, 0);
+        let (left, right) = line.split_at(line.len() / 2);
+        let left_chars: HashSet<char> = left.iter().copied().collect();
+        let right_chars: HashSet<char> = right.iter().copied().collect();
+        let intersection = left_chars.intersection(&right_chars).copied().collect_vec();
+        assert_eq!(intersection.len(), 1);
+        total_priority += char_to_priority(intersection[0]).unwrap();
+    }
+    total_priority
+}
+
+/// Reference `HashSet`-based implementation of part two.
+fn solve_part_two_hashset(data: &[String]) -> u32 {
+    let mut total_priority = 0;
+    for (a, b, c) in data.iter().tuples() {
+        let a_chars: HashSet<char> = a.chars().collect();
+        let b_chars: HashSet<char> = b.chars().collect();
+        let c_chars: HashSet<char> = c.chars().collect();
+        let intersection = intersection(vec![a_chars, b_chars, c_chars]);
+        assert_eq!(intersection.len(), 1);
+        let common = intersection.into_iter().next().unwrap();
+        total_priority += char_to_priority(common).unwrap();
+    }
+    total_priority
+}
+
+fn measure(name: &str, f: impl FnOnce() -> u32) -> u32 {
+    let start = Instant::now();
+    let result = f();
+    println!("{:>16}: {:>12} in {:?}", name, result, start.elapsed());
+    result
+}
+
+pub fn run(groups: usize) {
+    println!("==> Generating {} rucksacks...", 3 * groups);
+    let data = generate(groups, 42);
+    let table = PriorityTable::standard();
+
+    println!("==> Benchmarking part one...");
+    let a = measure("HashSet", || solve_part_one_hashset(&data));
+    let b = measure("ItemSet", || solve_part_one(&data, &table));
+    assert_eq!(a, b);
+
+    println!("==> Benchmarking part two...");
+    let a = measure("HashSet", || solve_part_two_hashset(&data));
+    let b = measure("ItemSet", || solve_part_two(&data, 3, &table).0);
+    assert_eq!(a, b);
+}
@@ -0,0 +1,143 @@
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::item_set::ItemSet;
+use crate::priority::PriorityTable;
+use crate::split_compartments;
+
+#[derive(Debug, Serialize)]
+pub struct RucksackReport {
+    /// 1-based line number.
+    pub line: usize,
+    pub left: String,
+    pub right: String,
+    pub shared: Vec<char>,
+    /// Priority of the shared item, if there is exactly one.
+    pub priority: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupReport {
+    /// 1-based line numbers of the rucksacks forming the group.
+    pub lines: Vec<usize>,
+    pub badge: Vec<char>,
+    /// Priority of the badge item, if there is exactly one.
+    pub priority: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Explanation {
+    pub rucksacks: Vec<RucksackReport>,
+    pub groups: Vec<GroupReport>,
+}
+
+fn single_priority(set: ItemSet) -> Option<u32> {
+    (set.count() == 1).then(|| set.iter_priorities().next().unwrap())
+}
+
+pub fn explain(data: &[String], group_size: usize, table: &PriorityTable) -> Explanation {
+    let rucksacks = data
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let (left, right) = split_compartments(line);
+            let common =
+                ItemSet::parse(left, table).unwrap() & ItemSet::parse(right, table).unwrap();
+            RucksackReport {
+                line: i + 1,
+                left: left.to_string(),
+                right: right.to_string(),
+                shared: common.iter_items(table).collect(),
+                priority: single_priority(common),
+            }
+        })
+        .collect_vec();
+
+    let groups = data
+        .chunks(group_size)
+        .enumerate()
+        .filter(|(_, group)| group.len() == group_size)
+        .map(|(i, group)| {
+            let common = group
+                .iter()
+                .map(|line| ItemSet::parse(line, table).unwrap())
+                .reduce(|a, b| a & b)
+                .unwrap();
+            GroupReport {
+                lines: (i * group_size + 1..=(i + 1) * group_size).collect(),
+                badge: common.iter_items(table).collect(),
+                priority: single_priority(common),
+            }
+        })
+        .collect_vec();
+
+    Explanation { rucksacks, groups }
+}
+
+impl Explanation {
+    pub fn print_text(&self) {
+        println!("==> Rucksacks...");
+        for r in &self.rucksacks {
+            println!(
+                "Line {}: {} | {} -> shared [{}], priority {}",
+                r.line,
+                r.left,
+                r.right,
+                r.shared.iter().join(""),
+                r.priority.map_or("-".to_string(), |p| p.to_string())
+            );
+        }
+        println!("==> Groups...");
+        for g in &self.groups {
+            println!(
+                "Lines {}: badge [{}], priority {}",
+                g.lines.iter().join(", "),
+                g.badge.iter().join(""),
+                g.priority.map_or("-".to_string(), |p| p.to_string())
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(s: &str) -> Vec<String> {
+        s.split_whitespace().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn sample_explanation() {
+        let data = lines(
+            "vJrwpWtwJgWrhcsFMMfFFhFp jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL PmmdzqPrVvPwwTWBwg \
+             wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn ttgJtRGJQctTZtZT CrZsJsPPZsGzwwsLwLmpwMDw abab",
+        );
+        let table = PriorityTable::standard();
+        let explanation = explain(&data, 3, &table);
+
+        let r = &explanation.rucksacks[0];
+        assert_eq!(
+            (r.left.as_str(), r.right.as_str()),
+            ("vJrwpWtwJgWr", "hcsFMMfFFhFp")
+        );
+        assert_eq!((r.shared.as_slice(), r.priority), (&['p'][..], Some(16)));
+        let r = &explanation.rucksacks[6];
+        assert_eq!(
+            (r.line, r.shared.as_slice(), r.priority),
+            (7, &['a', 'b'][..], None)
+        );
+
+        // The incomplete trailing group is left out.
+        assert_eq!(explanation.groups.len(), 2);
+        let g = &explanation.groups[1];
+        assert_eq!(g.lines, [4, 5, 6]);
+        assert_eq!((g.badge.as_slice(), g.priority), (&['Z'][..], Some(52)));
+
+        let json = serde_json::to_value(&explanation).unwrap();
+        assert_eq!(json["rucksacks"].as_array().unwrap().len(), 7);
+        assert_eq!(json["rucksacks"][6]["priority"], serde_json::Value::Null);
+        assert_eq!(json["groups"][0]["badge"], serde_json::json!(["r"]));
+        assert_eq!(json["groups"][0]["lines"], serde_json::json!([1, 2, 3]));
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{BitAnd, BitOr};
+use std::str::FromStr;
+
+use crate::priority::{PriorityTable, MAX_PRIORITY};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct InvalidItem(pub char);
+
+impl Display for InvalidItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid item: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidItem {}
+
+/// Set of rucksack items, stored as a bitmask where bit `p` is set
+/// iff the item with priority `p` is present.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct ItemSet(u128);
+
+impl ItemSet {
+    pub fn new() -> Self {
+        Self(0)
+    }
+
+    pub fn insert(&mut self, priority: u32) {
+        assert!(
+            priority <= MAX_PRIORITY,
+            "Priority {} does not fit into ItemSet",
+            priority
+        );
+        self.0 |= 1 << priority;
+    }
+
+    /// Parses the items of `s` using the given priority table.
+    pub fn parse(s: &str, table: &PriorityTable) -> Result<Self, InvalidItem> {
+        let mut set = ItemSet::new();
+        for c in s.chars() {
+            set.insert(table.priority(c).ok_or(InvalidItem(c))?);
+        }
+        Ok(set)
+    }
+
+    pub fn count(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// Iterates over the priorities of the items in ascending order.
+    pub fn iter_priorities(&self) -> impl Iterator<Item = u32> {
+        let mut bits = self.0;
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+            let p = bits.trailing_zeros();
+            bits &= bits - 1;
+            Some(p)
+        })
+    }
+
+    /// Iterates over the items in ascending order of priority.
+    pub fn iter_items<'a>(&self, table: &'a PriorityTable) -> impl Iterator<Item = char> + 'a {
+        self.iter_priorities().map(|p| table.item(p))
+    }
+}
+
+impl FromStr for ItemSet {
+    type Err = InvalidItem;
+
+    /// Parses the items of `s` using the [standard](PriorityTable::standard) priority table.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ItemSet::parse(s, &PriorityTable::standard())
+    }
+}
+
+impl BitAnd for ItemSet {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for ItemSet {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_operations() {
+        let a: ItemSet = "vJrwpWtwJgWr".parse().unwrap();
+        let b: ItemSet = "hcsFMMfFFhFp".parse().unwrap();
+        assert_eq!((a & b).iter_priorities().collect::<Vec<_>>(), vec![16]);
+        assert_eq!((a | b).count(), a.count() + b.count() - 1);
+    }
+
+    #[test]
+    fn invalid_item() {
+        let table = PriorityTable::standard();
+        assert_eq!(ItemSet::parse("ab1", &table), Err(InvalidItem('1')));
+        assert_eq!("ab1".parse::<ItemSet>(), Err(InvalidItem('1')));
+    }
+
+    #[test]
+    fn extended_table() {
+        let mut table = PriorityTable::standard();
+        table.insert('1', 53).unwrap();
+        table.insert('\u{e9}', 100).unwrap();
+        table.insert('\u{3bb}', MAX_PRIORITY).unwrap();
+        let set = ItemSet::parse("\u{3bb}ab1\u{e9}", &table).unwrap();
+        assert_eq!(
+            set.iter_items(&table).collect::<String>(),
+            "ab1\u{e9}\u{3bb}"
+        );
+        let other = ItemSet::parse("\u{3bb}\u{e9}z", &table).unwrap();
+        assert_eq!(
+            (set & other).iter_priorities().collect::<Vec<_>>(),
+            vec![100, MAX_PRIORITY]
+        );
+        assert!(table.insert('2', 1).is_err());
+    }
+}
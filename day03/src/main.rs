@@ -1,12 +1,21 @@
-use std::collections::HashSet;
-use std::convert::TryFrom;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use color_eyre::eyre::bail;
 use itertools::Itertools;
 
+use crate::item_set::ItemSet;
+use crate::priority::PriorityTable;
+use crate::validate::validate;
+
+mod bench;
+mod explain;
+mod item_set;
+mod priority;
+mod validate;
+
 #[derive(Parser, Debug)]
 #[command(author, version)]
 struct Cli {
@@ -14,105 +23,204 @@ struct Cli {
     #[arg(value_name = "FILE")]
     #[arg(default_value = "data/sample.txt")]
     input: PathBuf,
-}
 
-fn char_to_priority(c: char) -> u32 {
-    if matches!(c, 'a'..='z') {
-        (c as u8 - 'a' as u8) as u32 + 1
-    } else if matches!(c, 'A'..='Z') {
-        (c as u8 - 'A' as u8) as u32 + 27
-    } else {
-        panic!("Invalid character: {}", c);
-    }
+    /// Benchmark against the HashSet-based solution on N generated groups
+    #[arg(long, value_name = "N")]
+    bench: Option<usize>,
+
+    /// Number of rucksacks in each group
+    #[arg(short, long)]
+    #[arg(default_value_t = 3)]
+    #[arg(value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    group_size: usize,
+
+    /// Explain the shared item of each rucksack and the badge of each group
+    /// (in JSON, only the explanation is printed)
+    #[arg(long, value_name = "FORMAT")]
+    #[arg(num_args = 0..=1, default_missing_value = "text")]
+    explain: Option<ExplainFormat>,
+
+    /// Extra item priorities, one `<item> <priority>` pair per line, with priorities
+    /// of at most 127 (the capacity of an item set)
+    #[arg(long, value_name = "FILE")]
+    priorities: Option<PathBuf>,
 }
 
-struct ChunkIter<'a, T, const N: usize> {
-    data: &'a [T],
-    index: usize,
-}
-
-impl<'a, T, const N: usize> Iterator for ChunkIter<'a, T, N> {
-    type Item = &'a [T; N];
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.index >= self.data.len() {
-            return None;
-        }
-        let chunk = <&[T; N]>::try_from(&self.data[self.index..self.index + N]).unwrap();
-        self.index += N;
-        Some(chunk)
-    }
+#[derive(ValueEnum, Copy, Clone, Debug)]
+enum ExplainFormat {
+    Text,
+    Json,
 }
 
-/// **Usage:**
-///
-/// ```
-/// // &data: &[T]
-/// for block in chunks::<_, 3>(&data) {
-///     // block: [T; 3]
-/// }
-/// ```
-fn chunks<T, const N: usize>(data: &[T]) -> ChunkIter<T, N> {
-    assert_eq!(data.len() % N, 0);
-    ChunkIter { data, index: 0 }
+/// Splits the rucksack into two compartments with the same number of items.
+fn split_compartments(line: &str) -> (&str, &str) {
+    let half = line.chars().count() / 2;
+    let mid = line.char_indices().nth(half).map_or(line.len(), |(i, _)| i);
+    line.split_at(mid)
 }
 
-fn intersection(mut sets: Vec<HashSet<char>>) -> HashSet<char> {
-    if sets.is_empty() {
-        return HashSet::new();
+fn solve_part_one(data: &[String], table: &PriorityTable) -> u32 {
+    let mut total_priority = 0;
+    for line in data {
+        let (left, right) = split_compartments(line);
+        let left_items = ItemSet::parse(left, table).unwrap();
+        let right_items = ItemSet::parse(right, table).unwrap();
+        let common = left_items & right_items;
+        assert_eq!(common.count(), 1);
+        total_priority += common.iter_priorities().next().unwrap();
     }
+    total_priority
+}
 
-    if sets.len() == 1 {
-        return sets.pop().unwrap();
+/// Sums the badge priorities of all groups, along with warnings about the groups skipped.
+fn solve_part_two(data: &[String], group_size: usize, table: &PriorityTable) -> (u32, Vec<String>) {
+    let mut total_priority = 0;
+    let mut warnings = Vec::new();
+    for (i, group) in data.chunks(group_size).enumerate() {
+        let first_line = i * group_size + 1;
+        let last_line = first_line + group.len() - 1;
+        if group.len() < group_size {
+            warnings.push(format!(
+                "incomplete trailing group of {} rucksack(s) on lines {}..={}, skipping",
+                group.len(),
+                first_line,
+                last_line
+            ));
+            continue;
+        }
+        let common = group
+            .iter()
+            .map(|line| ItemSet::parse(line, table).unwrap())
+            .reduce(|a, b| a & b)
+            .unwrap();
+        if common.count() != 1 {
+            warnings.push(format!(
+                "group on lines {}..={} has {} common items [{}], skipping",
+                first_line,
+                last_line,
+                common.count(),
+                common.iter_items(table).join("")
+            ));
+            continue;
+        }
+        total_priority += common.iter_priorities().next().unwrap();
     }
-
-    let mut result = sets.pop().unwrap();
-    result.retain(|item| sets.iter().all(|set| set.contains(item)));
-    result
+    (total_priority, warnings)
 }
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
 
     let args = Cli::parse();
-    println!("args = {:?}", args);
+    // A JSON explanation is printed alone, so the output can be redirected to a file.
+    let json = matches!(args.explain, Some(ExplainFormat::Json));
+    if !json {
+        println!("args = {:?}", args);
+    }
+
+    if let Some(groups) = args.bench {
+        bench::run(groups);
+        return Ok(());
+    }
 
     let path = args.input;
 
     let data = BufReader::new(File::open(path)?)
         .lines()
-        .filter_map(|x| x.ok())
-        .map(|line| line.chars().collect_vec())
+        .map_while(Result::ok)
         .collect_vec();
 
-    println!("==> Solving part one...");
-    let mut total_priority1 = 0;
-    for line in &data {
-        assert_eq!(line.len() % 2, 0);
-        let (left, right) = line.split_at(line.len() / 2);
-        let left_chars: HashSet<char> = left.iter().copied().collect();
-        let right_chars: HashSet<char> = right.iter().copied().collect();
-        let intersection = left_chars.intersection(&right_chars).copied().collect_vec();
-        assert_eq!(intersection.len(), 1);
-        let common = intersection[0];
-        let priority = char_to_priority(common);
-        total_priority1 += priority;
+    let mut table = PriorityTable::standard();
+    if let Some(path) = &args.priorities {
+        table.extend_from_file(path)?;
     }
+
+    let errors = validate(&data, &table);
+    if !errors.is_empty() {
+        for error in &errors {
+            println!("Line {}: {}", error.line, error.reasons.join("; "));
+        }
+        bail!("Found {} invalid line(s)", errors.len());
+    }
+
+    if let Some(format) = args.explain {
+        let explanation = explain::explain(&data, args.group_size, &table);
+        match format {
+            ExplainFormat::Text => explanation.print_text(),
+            ExplainFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(&explanation)?);
+                return Ok(());
+            }
+        }
+    }
+
+    println!("==> Solving part one...");
+    let total_priority1 = solve_part_one(&data, &table);
     println!("Total priority: {}", total_priority1);
 
     println!("==> Solving part two...");
-    let mut total_priority2 = 0;
-    for [a, b, c] in chunks::<_, 3>(&data) {
-        let a_chars: HashSet<char> = a.iter().copied().collect();
-        let b_chars: HashSet<char> = b.iter().copied().collect();
-        let c_chars: HashSet<char> = c.iter().copied().collect();
-        let intersection = intersection(vec![a_chars, b_chars, c_chars]);
-        assert_eq!(intersection.len(), 1);
-        let common = intersection.into_iter().next().unwrap();
-        let priority = char_to_priority(common);
-        total_priority2 += priority;
+    let (total_priority2, warnings) = solve_part_two(&data, args.group_size, &table);
+    for warning in &warnings {
+        println!("Warning: {}", warning);
     }
     println!("Total priority: {}", total_priority2);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(s: &str) -> Vec<String> {
+        s.split_whitespace().map(|line| line.to_string()).collect()
+    }
+
+    const SAMPLE: &str = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw";
+
+    #[test]
+    fn sample_groups() {
+        let table = PriorityTable::standard();
+        assert_eq!(solve_part_one(&lines(SAMPLE), &table), 157);
+        assert_eq!(solve_part_two(&lines(SAMPLE), 3, &table), (70, vec![]));
+    }
+
+    #[test]
+    fn incomplete_trailing_group() {
+        let table = PriorityTable::standard();
+        let data = lines(&format!("{} abab", SAMPLE));
+        let (total, warnings) = solve_part_two(&data, 3, &table);
+        assert_eq!(total, 70);
+        assert_eq!(
+            warnings,
+            ["incomplete trailing group of 1 rucksack(s) on lines 7..=7, skipping"]
+        );
+        // A single group of all 7 rucksacks is incomplete as a whole.
+        let (total, warnings) = solve_part_two(&data, 8, &table);
+        assert_eq!(total, 0);
+        assert_eq!(
+            warnings,
+            ["incomplete trailing group of 7 rucksack(s) on lines 1..=7, skipping"]
+        );
+    }
+
+    #[test]
+    fn groups_without_single_badge() {
+        let table = PriorityTable::standard();
+        let data = lines("abcd efgh abcd bacx aXbY XzXz");
+        let (total, warnings) = solve_part_two(&data, 2, &table);
+        assert_eq!(total, 50);
+        assert_eq!(
+            warnings,
+            [
+                "group on lines 1..=2 has 0 common items [], skipping",
+                "group on lines 3..=4 has 3 common items [abc], skipping",
+            ]
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use color_eyre::eyre::{bail, eyre, WrapErr};
+
+/// Largest priority an [`ItemSet`](crate::item_set::ItemSet) can hold.
+pub const MAX_PRIORITY: u32 = 127;
+
+/// Returns the standard priority of the item `c`: `a-z` map to 1..=26, `A-Z` to 27..=52.
+pub fn char_to_priority(c: char) -> Option<u32> {
+    match c {
+        'a'..='z' => Some((c as u8 - b'a') as u32 + 1),
+        'A'..='Z' => Some((c as u8 - b'A') as u32 + 27),
+        _ => None,
+    }
+}
+
+/// Mapping between items and their priorities.
+#[derive(Debug, Clone)]
+pub struct PriorityTable {
+    /// Fast path for ASCII items.
+    ascii: [Option<u32>; 128],
+    /// Non-ASCII items.
+    other: HashMap<char, u32>,
+    items: [Option<char>; MAX_PRIORITY as usize + 1],
+}
+
+impl PriorityTable {
+    pub fn empty() -> Self {
+        Self {
+            ascii: [None; 128],
+            other: HashMap::new(),
+            items: [None; MAX_PRIORITY as usize + 1],
+        }
+    }
+
+    /// The puzzle's table: `a-z` and `A-Z`.
+    pub fn standard() -> Self {
+        let mut table = Self::empty();
+        for c in ('a'..='z').chain('A'..='Z') {
+            table.insert(c, char_to_priority(c).unwrap()).unwrap();
+        }
+        table
+    }
+
+    pub fn insert(&mut self, item: char, priority: u32) -> color_eyre::Result<()> {
+        if !(1..=MAX_PRIORITY).contains(&priority) {
+            bail!("Priority {} is out of range 1..={}", priority, MAX_PRIORITY);
+        }
+        if let Some(other) = self.items[priority as usize] {
+            if other != item {
+                bail!("Priority {} is already used by {:?}", priority, other);
+            }
+        }
+        let old = if item.is_ascii() {
+            self.ascii[item as usize].replace(priority)
+        } else {
+            self.other.insert(item, priority)
+        };
+        if let Some(old) = old {
+            self.items[old as usize] = None;
+        }
+        self.items[priority as usize] = Some(item);
+        Ok(())
+    }
+
+    /// Extends the table with the mapping from `path`.
+    ///
+    /// Each non-empty line has the form `<item> <priority>`, lines starting with `#` are ignored.
+    pub fn extend_from_file(&mut self, path: &Path) -> color_eyre::Result<()> {
+        let content = read_to_string(path)
+            .wrap_err_with(|| format!("Could not read priority table {}", path.display()))?;
+        self.extend_from_str(&content)
+    }
+
+    /// Same as [`extend_from_file`](Self::extend_from_file), for the contents of the file.
+    fn extend_from_str(&mut self, content: &str) -> color_eyre::Result<()> {
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parse = || -> color_eyre::Result<(char, u32)> {
+                let (item, priority) = line
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(|| eyre!("Expected '<item> <priority>'"))?;
+                let mut chars = item.chars();
+                let (Some(item), None) = (chars.next(), chars.next()) else {
+                    bail!("Item must be a single character, got {:?}", item);
+                };
+                let priority = priority.trim().parse::<u32>()?;
+                Ok((item, priority))
+            };
+            let (item, priority) = parse().wrap_err_with(|| format!("Line {}", i + 1))?;
+            self.insert(item, priority)
+                .wrap_err_with(|| format!("Line {}", i + 1))?;
+        }
+        Ok(())
+    }
+
+    pub fn priority(&self, item: char) -> Option<u32> {
+        if item.is_ascii() {
+            self.ascii[item as usize]
+        } else {
+            self.other.get(&item).copied()
+        }
+    }
+
+    pub fn item(&self, priority: u32) -> char {
+        self.items
+            .get(priority as usize)
+            .copied()
+            .flatten()
+            .unwrap_or_else(|| panic!("Invalid priority: {}", priority))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The error message with its context, e.g. `Line 2: Invalid item`.
+    fn error(table: &mut PriorityTable, content: &str) -> String {
+        let e = table.extend_from_str(content).unwrap_err();
+        e.chain()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join(": ")
+    }
+
+    #[test]
+    fn extend_from_file() {
+        let mut table = PriorityTable::standard();
+        table
+            .extend_from_str("# digits\n\n1 53\n\u{e9} 127\n")
+            .unwrap();
+        assert_eq!(table.priority('1'), Some(53));
+        assert_eq!(table.item(127), '\u{e9}');
+
+        assert_eq!(
+            error(&mut table, "2 54\n3"),
+            "Line 2: Expected '<item> <priority>'"
+        );
+        assert_eq!(
+            error(&mut table, "ab 54"),
+            "Line 1: Item must be a single character, got \"ab\""
+        );
+        assert!(error(&mut table, "\n\n2 x").starts_with("Line 3: invalid digit"));
+        assert_eq!(
+            error(&mut table, "2 128"),
+            "Line 1: Priority 128 is out of range 1..=127"
+        );
+        assert_eq!(
+            error(&mut table, "2 1"),
+            "Line 1: Priority 1 is already used by 'a'"
+        );
+    }
+
+    #[test]
+    fn missing_file() {
+        let e = PriorityTable::standard()
+            .extend_from_file(Path::new("no/such/table.txt"))
+            .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Could not read priority table no/such/table.txt"
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use itertools::Itertools;
+
+use crate::item_set::ItemSet;
+use crate::priority::PriorityTable;
+use crate::split_compartments;
+
+#[derive(Debug)]
+pub struct LineError {
+    /// 1-based line number.
+    pub line: usize,
+    pub reasons: Vec<String>,
+}
+
+/// Checks every rucksack and collects all problems found, instead of stopping at the first one.
+pub fn validate(data: &[String], table: &PriorityTable) -> Vec<LineError> {
+    let mut errors = Vec::new();
+    for (i, line) in data.iter().enumerate() {
+        let mut reasons = Vec::new();
+
+        let len = line.chars().count();
+        if len % 2 != 0 {
+            reasons.push(format!("odd number of items ({})", len));
+        }
+        for (col, c) in line.chars().enumerate() {
+            if table.priority(c).is_none() {
+                reasons.push(format!("unknown item {:?} at column {}", c, col + 1));
+            }
+        }
+
+        if reasons.is_empty() {
+            let (left, right) = split_compartments(line);
+            let common =
+                ItemSet::parse(left, table).unwrap() & ItemSet::parse(right, table).unwrap();
+            if common.count() != 1 {
+                reasons.push(format!(
+                    "compartments share {} items [{}] instead of one",
+                    common.count(),
+                    common.iter_items(table).join("")
+                ));
+            }
+        }
+
+        if !reasons.is_empty() {
+            errors.push(LineError {
+                line: i + 1,
+                reasons,
+            });
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_all_bad_lines() {
+        let data = [
+            "vJrwpWtwJgWrhcsFMMfFFhFp",
+            "abc",
+            "ab1!",
+            "a1b",
+            "abab",
+            "aa",
+        ]
+        .map(String::from);
+        let errors = validate(&data, &PriorityTable::standard());
+        let errors = errors
+            .iter()
+            .map(|e| (e.line, e.reasons.join("; ")))
+            .collect_vec();
+        assert_eq!(
+            errors,
+            [
+                (2, "odd number of items (3)".to_string()),
+                (
+                    3,
+                    "unknown item '1' at column 3; unknown item '!' at column 4".to_string()
+                ),
+                (
+                    4,
+                    "odd number of items (3); unknown item '1' at column 2".to_string()
+                ),
+                (
+                    5,
+                    "compartments share 2 items [ab] instead of one".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::BTreeSet;
+
+use common::interval::{Interval, IntervalSet};
+use itertools::Itertools;
+
+/// Section assignment of a single elf.
+#[derive(Debug, Copy, Clone)]
+pub struct Assignment {
+    /// 1-based line number.
+    pub line: usize,
+    /// Index of the elf within the line.
+    pub elf: usize,
+    pub interval: Interval<u32>,
+}
+
+#[derive(Debug)]
+pub struct Analysis {
+    pub overlapping_pairs: u64,
+    pub containing_pairs: u64,
+    /// Maximum number of elves covering a single section, and the first such section.
+    pub max_coverage: Option<(usize, u32)>,
+    /// Sections between the lowest and the highest assigned ones that nobody covers.
+    pub uncovered: IntervalSet<u32>,
+}
+
+/// Fenwick tree over counts, used to count containing pairs.
+struct Fenwick(Vec<u64>);
+
+impl Fenwick {
+    fn new(n: usize) -> Self {
+        Self(vec![0; n + 1])
+    }
+
+    fn add(&mut self, i: usize) {
+        let mut i = i + 1;
+        while i < self.0.len() {
+            self.0[i] += 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Sum over `0..i`.
+    fn prefix(&self, i: usize) -> u64 {
+        let mut i = i;
+        let mut sum = 0;
+        while i > 0 {
+            sum += self.0[i];
+            i -= i & i.wrapping_neg();
+        }
+        sum
+    }
+}
+
+/// Assignments sorted by start ascending, then by end descending, so that
+/// an assignment can only be contained in the ones preceding it.
+fn sweep_order(assignments: &[Assignment]) -> Vec<&Assignment> {
+    assignments
+        .iter()
+        .sorted_unstable_by_key(|a| (a.interval.start, std::cmp::Reverse(a.interval.end)))
+        .collect()
+}
+
+pub fn analyze(assignments: &[Assignment]) -> Analysis {
+    let sorted = sweep_order(assignments);
+
+    // All intervals ending before `start` also start before it, so they precede
+    // the current one in the sweep order; all other preceding intervals overlap it.
+    let ends = assignments
+        .iter()
+        .map(|a| a.interval.end)
+        .sorted_unstable()
+        .collect_vec();
+    let mut overlapping_pairs = 0;
+    for (j, a) in sorted.iter().enumerate() {
+        let ended = ends.partition_point(|&e| e < a.interval.start);
+        overlapping_pairs += (j - ended) as u64;
+    }
+
+    // Preceding intervals with `end >= a.end` contain `a`.
+    let distinct_ends = ends.iter().copied().dedup().collect_vec();
+    let mut fenwick = Fenwick::new(distinct_ends.len());
+    let mut containing_pairs = 0;
+    for (j, a) in sorted.iter().enumerate() {
+        let k = distinct_ends.binary_search(&a.interval.end).unwrap();
+        containing_pairs += j as u64 - fenwick.prefix(k);
+        fenwick.add(k);
+    }
+
+    // Coverage: +1 at start, -1 right after the end.
+    let events = assignments
+        .iter()
+        .flat_map(|a| {
+            [
+                (a.interval.start as u64, 1),
+                (a.interval.end as u64 + 1, -1),
+            ]
+        })
+        .sorted_unstable()
+        .collect_vec();
+    let mut max_coverage: Option<(usize, u32)> = None;
+    let mut current = 0i64;
+    for (pos, delta) in events {
+        current += delta;
+        if max_coverage.is_none_or(|(m, _)| current as usize > m) {
+            max_coverage = Some((current as usize, pos as u32));
+        }
+    }
+
+    let covered: IntervalSet<u32> = assignments.iter().map(|a| a.interval).collect();
+    let uncovered = match (covered.iter().next(), covered.iter().last()) {
+        (Some(first), Some(last)) => covered.complement(Interval::new(first.start, last.end)),
+        _ => IntervalSet::new(),
+    };
+
+    Analysis {
+        overlapping_pairs,
+        containing_pairs,
+        max_coverage,
+        uncovered,
+    }
+}
+
+/// Calls `f(a, b, contains)` for every pair of overlapping assignments,
+/// where `contains` tells whether one of them contains the other.
+///
+/// Runs in O(n log n + k), where k is the number of reported pairs.
+pub fn for_each_overlapping_pair(
+    assignments: &[Assignment],
+    mut f: impl FnMut(&Assignment, &Assignment, bool),
+) {
+    let sorted = sweep_order(assignments);
+    // Active intervals, keyed by (end, index in `sorted`).
+    let mut active: BTreeSet<(u32, usize)> = BTreeSet::new();
+    for (j, a) in sorted.iter().enumerate() {
+        while let Some(&(end, _)) = active.first() {
+            if end >= a.interval.start {
+                break;
+            }
+            active.pop_first();
+        }
+        for &(end, i) in &active {
+            f(sorted[i], a, end >= a.interval.end);
+        }
+        active.insert((a.interval.end, j));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sweep_matches_brute_force() {
+        let intervals = [
+            (2, 4),
+            (6, 8),
+            (2, 3),
+            (4, 5),
+            (5, 7),
+            (7, 9),
+            (2, 8),
+            (3, 7),
+            (6, 6),
+        ];
+        let assignments = intervals
+            .iter()
+            .enumerate()
+            .map(|(i, &(a, b))| Assignment {
+                line: i + 1,
+                elf: 0,
+                interval: Interval::new(a, b),
+            })
+            .collect_vec();
+
+        let mut overlapping = 0;
+        let mut containing = 0;
+        for (a, b) in assignments.iter().tuple_combinations() {
+            if a.interval.overlaps(&b.interval) {
+                overlapping += 1;
+            }
+            if a.interval.contains_interval(&b.interval)
+                || b.interval.contains_interval(&a.interval)
+            {
+                containing += 1;
+            }
+        }
+
+        let analysis = analyze(&assignments);
+        assert_eq!(analysis.overlapping_pairs, overlapping);
+        assert_eq!(analysis.containing_pairs, containing);
+
+        let mut listed = 0;
+        let mut listed_containing = 0;
+        for_each_overlapping_pair(&assignments, |_, _, contains| {
+            listed += 1;
+            listed_containing += contains as u64;
+        });
+        assert_eq!(listed, overlapping);
+        assert_eq!(listed_containing, containing);
+        assert_eq!(analysis.max_coverage, Some((5, 6)));
+    }
+}
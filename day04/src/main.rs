@@ -2,9 +2,18 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use color_eyre::eyre::bail;
 use itertools::Itertools;
 
+use crate::analysis::{analyze, for_each_overlapping_pair, Assignment};
+use crate::parser::parse_line;
+use crate::planner::{plan_lines, plan_roster, write_roster, Edit};
+
+mod analysis;
+mod parser;
+mod planner;
+
 #[derive(Parser, Debug)]
 #[command(author, version)]
 struct Cli {
@@ -12,6 +21,33 @@ struct Cli {
     #[arg(value_name = "FILE")]
     #[arg(default_value = "data/sample.txt")]
     input: PathBuf,
+
+    /// Analyze all assignments in the file as a single population
+    #[arg(long)]
+    analyze: bool,
+
+    /// With --analyze, also list every overlapping pair
+    #[arg(long, requires = "analyze")]
+    list_pairs: bool,
+
+    /// Swap reversed ranges such as `6-2` (with a warning) instead of rejecting them
+    #[arg(long)]
+    normalize: bool,
+
+    /// Plan the minimal edits removing overlaps, within each line or across the whole roster
+    #[arg(long, value_name = "SCOPE")]
+    plan: Option<PlanScope>,
+
+    /// Write the edited roster to FILE instead of stdout
+    #[arg(long, value_name = "FILE", requires = "plan")]
+    plan_output: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug)]
+enum PlanScope {
+    #[value(alias = "pairs")]
+    Lines,
+    Roster,
 }
 
 fn main() -> color_eyre::Result<()> {
@@ -22,46 +58,150 @@ fn main() -> color_eyre::Result<()> {
 
     let path = args.input;
 
-    let data = BufReader::new(File::open(path)?)
+    let mut data = Vec::new();
+    let mut errors = Vec::new();
+    for (i, line) in BufReader::new(File::open(path)?)
         .lines()
-        .filter_map(|x| x.ok())
-        .map(|line| {
-            let xs = line
-                .split(',')
-                .map(|s| {
-                    let ys = s.split('-').collect_vec();
-                    assert_eq!(ys.len(), 2);
-                    let a = ys[0].parse::<u32>().unwrap();
-                    let b = ys[1].parse::<u32>().unwrap();
-                    assert!(a <= b);
-                    (a, b)
-                })
-                .collect_vec();
-            assert_eq!(xs.len(), 2);
-            let a = xs[0];
-            let b = xs[1];
-            (a, b)
-        })
-        .collect_vec();
+        .map_while(Result::ok)
+        .enumerate()
+    {
+        match parse_line(&line, args.normalize) {
+            Ok(parsed) => {
+                for warning in &parsed.warnings {
+                    println!("Warning: line {}: {}", i + 1, warning);
+                }
+                data.push(parsed.assignments);
+            }
+            Err(error) => errors.push(format!("Line {}: {}", i + 1, error)),
+        }
+    }
+    if !errors.is_empty() {
+        for error in &errors {
+            println!("{}", error);
+        }
+        bail!("Found {} malformed line(s)", errors.len());
+    }
     println!("Data size: {}", data.len());
 
     println!("==> Solving part one...");
     let mut count_contains = 0;
-    for (a, b) in &data {
-        if (a.0 <= b.0 && b.1 <= a.1) || (b.0 <= a.0 && a.1 <= b.1) {
-            count_contains += 1;
+    for line in &data {
+        for (a, b) in line.iter().tuple_combinations() {
+            if a.contains_interval(b) || b.contains_interval(a) {
+                count_contains += 1;
+            }
         }
     }
     println!("Number of fully contained intervals: {}", count_contains);
 
     println!("==> Solving part two...");
     let mut count_overlap = 0;
-    for (a, b) in &data {
-        if (a.0 <= b.0 && b.0 <= a.1) || (b.0 <= a.0 && a.0 <= b.1) {
-            count_overlap += 1;
+    for line in &data {
+        for (a, b) in line.iter().tuple_combinations() {
+            if a.overlaps(b) {
+                count_overlap += 1;
+            }
         }
     }
     println!("Number of overlapped intervals: {}", count_overlap);
 
+    let wide_lines = data.iter().enumerate().filter(|(_, line)| line.len() > 2);
+    for (i, line) in wide_lines {
+        let pairs = line.iter().tuple_combinations::<(_, _)>().collect_vec();
+        println!(
+            "Line {}: {} assignments, {} pairs, {} contained, {} overlapped",
+            i + 1,
+            line.len(),
+            pairs.len(),
+            pairs
+                .iter()
+                .filter(|(a, b)| a.contains_interval(b) || b.contains_interval(a))
+                .count(),
+            pairs.iter().filter(|(a, b)| a.overlaps(b)).count()
+        );
+    }
+
+    if args.analyze {
+        println!("==> Analyzing all assignments...");
+        let assignments = data
+            .iter()
+            .enumerate()
+            .flat_map(|(i, line)| {
+                line.iter()
+                    .copied()
+                    .enumerate()
+                    .map(move |(elf, interval)| Assignment {
+                        line: i + 1,
+                        elf,
+                        interval,
+                    })
+            })
+            .collect_vec();
+        let analysis = analyze(&assignments);
+        println!("Overlapping pairs: {}", analysis.overlapping_pairs);
+        println!("Containing pairs: {}", analysis.containing_pairs);
+        if let Some((count, section)) = analysis.max_coverage {
+            println!(
+                "Max coverage: {} elves (first at section {})",
+                count, section
+            );
+        }
+        println!(
+            "Uncovered sections: {} [{}]",
+            analysis.uncovered.len(),
+            analysis.uncovered.iter().join(", ")
+        );
+        if args.list_pairs {
+            for_each_overlapping_pair(&assignments, |a, b, contains| {
+                println!(
+                    "{}:{} ({}) {} {}:{} ({})",
+                    a.line,
+                    a.elf,
+                    a.interval,
+                    if contains { "contains" } else { "overlaps" },
+                    b.line,
+                    b.elf,
+                    b.interval
+                );
+            });
+        }
+    }
+
+    if let Some(scope) = args.plan {
+        println!("==> Planning reassignments ({:?})...", scope);
+        let plan = match scope {
+            PlanScope::Lines => plan_lines(&data),
+            PlanScope::Roster => plan_roster(&data),
+        };
+        let mut shrunk = 0;
+        let mut dropped = 0;
+        for (i, (line, edits)) in data.iter().zip(&plan).enumerate() {
+            for (x, edit) in line.iter().zip(edits) {
+                match edit {
+                    Edit::Keep => {}
+                    Edit::Shrink(new) => {
+                        shrunk += 1;
+                        println!("Line {}: shrink {} to {}", i + 1, x, new);
+                    }
+                    Edit::Drop => {
+                        dropped += 1;
+                        println!("Line {}: drop {}", i + 1, x);
+                    }
+                }
+            }
+        }
+        println!("Edits: {} shrunk, {} dropped", shrunk, dropped);
+        match &args.plan_output {
+            Some(path) => {
+                write_roster(File::create(path)?, &data, &plan)?;
+                println!("Edited roster written to {}", path.display());
+            }
+            None => {
+                println!("==> Edited roster...");
+                write_roster(std::io::stdout().lock(), &data, &plan)?;
+            }
+        }
+    }
+
     Ok(())
 }
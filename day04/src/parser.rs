@@ -0,0 +1,98 @@
+use common::interval::Interval;
+
+/// Parsed line with any warnings produced while parsing it.
+#[derive(Debug)]
+pub struct ParsedLine {
+    pub assignments: Vec<Interval<u32>>,
+    pub warnings: Vec<String>,
+}
+
+fn parse_number(s: &str) -> Result<u32, String> {
+    s.trim()
+        .parse::<u32>()
+        .map_err(|e| format!("bad section number {:?}: {}", s, e))
+}
+
+/// Parses a range `a-b`. Reversed ranges are swapped if `normalize` is set, and rejected otherwise.
+fn parse_range(
+    s: &str,
+    normalize: bool,
+    warnings: &mut Vec<String>,
+) -> Result<Interval<u32>, String> {
+    let (a, b) = s
+        .split_once('-')
+        .ok_or_else(|| format!("bad range {:?}: expected 'a-b'", s))?;
+    let a = parse_number(a)?;
+    let b = parse_number(b)?;
+    if a <= b {
+        Ok(Interval::new(a, b))
+    } else if normalize {
+        warnings.push(format!(
+            "reversed range {}-{} normalized to {}-{}",
+            a, b, b, a
+        ));
+        Ok(Interval::new(b, a))
+    } else {
+        Err(format!("reversed range {}-{}", a, b))
+    }
+}
+
+/// Parses a line of comma-separated ranges. An empty line has no assignments.
+pub fn parse_line(line: &str, normalize: bool) -> Result<ParsedLine, String> {
+    let mut warnings = Vec::new();
+    let assignments = if line.trim().is_empty() {
+        Vec::new()
+    } else {
+        line.split(',')
+            .map(|s| parse_range(s, normalize, &mut warnings))
+            .collect::<Result<_, _>>()?
+    };
+    Ok(ParsedLine {
+        assignments,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reversed_ranges() {
+        let parsed = parse_line("6-2,4-8", true).unwrap();
+        assert_eq!(
+            parsed.assignments,
+            [Interval::new(2, 6), Interval::new(4, 8)]
+        );
+        assert_eq!(parsed.warnings, ["reversed range 6-2 normalized to 2-6"]);
+        assert_eq!(
+            parse_line("6-2,4-8", false).unwrap_err(),
+            "reversed range 6-2"
+        );
+        assert!(parse_line("3-3", false).unwrap().warnings.is_empty());
+    }
+
+    #[test]
+    fn malformed_numbers() {
+        assert_eq!(
+            parse_line("2-x,4-8", true).unwrap_err(),
+            "bad section number \"x\": invalid digit found in string"
+        );
+        assert_eq!(
+            parse_line("2-4,5", true).unwrap_err(),
+            "bad range \"5\": expected 'a-b'"
+        );
+        for line in ["-4,5-6", "2-4,5-", "1-99999999999", "2-4;5-6", "2-4,,5-6"] {
+            assert!(parse_line(line, true).is_err(), "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn any_number_of_ranges() {
+        let parsed = parse_line("1-2, 3 - 4,5-6,7-8", false).unwrap();
+        assert_eq!(parsed.assignments.len(), 4);
+        assert_eq!(parsed.assignments[1], Interval::new(3, 4));
+        assert_eq!(parse_line("5-9", false).unwrap().assignments.len(), 1);
+        assert!(parse_line("  ", false).unwrap().assignments.is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+use std::io::Write;
+
+use common::interval::{Interval, IntervalSet};
+use itertools::Itertools;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Edit {
+    Keep,
+    Shrink(Interval<u32>),
+    Drop,
+}
+
+impl Edit {
+    /// Resulting assignment, if any.
+    pub fn apply(&self, interval: Interval<u32>) -> Option<Interval<u32>> {
+        match *self {
+            Edit::Keep => Some(interval),
+            Edit::Shrink(new) => Some(new),
+            Edit::Drop => None,
+        }
+    }
+}
+
+/// Largest part of `interval` not covered by `occupied`.
+fn largest_free_part(
+    interval: Interval<u32>,
+    occupied: &IntervalSet<u32>,
+) -> Option<Interval<u32>> {
+    let whole: IntervalSet<u32> = std::iter::once(interval).collect();
+    whole
+        .difference(occupied)
+        .iter()
+        .copied()
+        .max_by_key(|part| part.len())
+}
+
+/// Cheapest edit of `interval` making it disjoint from `occupied`, and its cost in lost sections.
+fn cheapest_edit(interval: Interval<u32>, occupied: &IntervalSet<u32>) -> (Edit, u64) {
+    match largest_free_part(interval, occupied) {
+        Some(part) => (Edit::Shrink(part), interval.len() - part.len()),
+        None => (Edit::Drop, interval.len()),
+    }
+}
+
+/// Edits the fewest of the given assignments so that no two of them overlap.
+///
+/// The untouched assignments must be pairwise disjoint, so the largest such set is chosen
+/// greedily by earliest end. Every other assignment is then shrunk to its largest free
+/// part, or dropped if nothing is left.
+fn plan_group(intervals: &[Interval<u32>]) -> Vec<Edit> {
+    let mut plan = vec![Edit::Keep; intervals.len()];
+
+    // For a single pair, exactly one edit is needed: pick the one losing fewer sections.
+    if let [a, b] = *intervals {
+        if a.overlaps(&b) {
+            let (edit_a, cost_a) = cheapest_edit(a, &std::iter::once(b).collect());
+            let (edit_b, cost_b) = cheapest_edit(b, &std::iter::once(a).collect());
+            if cost_a <= cost_b {
+                plan[0] = edit_a;
+            } else {
+                plan[1] = edit_b;
+            }
+        }
+        return plan;
+    }
+
+    let order = (0..intervals.len())
+        .sorted_unstable_by_key(|&i| (intervals[i].end, intervals[i].start))
+        .collect_vec();
+    let mut kept = Vec::new();
+    let mut rest = Vec::new();
+    let mut last_end: Option<u32> = None;
+    for i in order {
+        let x = intervals[i];
+        if last_end.is_none_or(|end| x.start > end) {
+            kept.push(x);
+            last_end = Some(x.end);
+        } else {
+            rest.push(i);
+        }
+    }
+
+    let mut occupied: IntervalSet<u32> = kept.into_iter().collect();
+    for i in rest {
+        let (edit, _) = cheapest_edit(intervals[i], &occupied);
+        if let Edit::Shrink(part) = edit {
+            occupied.insert(part);
+        }
+        plan[i] = edit;
+    }
+    plan
+}
+
+/// Removes overlaps within each line separately.
+pub fn plan_lines(data: &[Vec<Interval<u32>>]) -> Vec<Vec<Edit>> {
+    data.iter().map(|line| plan_group(line)).collect()
+}
+
+/// Removes overlaps across the whole roster.
+pub fn plan_roster(data: &[Vec<Interval<u32>>]) -> Vec<Vec<Edit>> {
+    let flat = data.iter().flatten().copied().collect_vec();
+    let mut edits = plan_group(&flat).into_iter();
+    data.iter()
+        .map(|line| edits.by_ref().take(line.len()).collect())
+        .collect()
+}
+
+/// Writes the roster with the edits applied, in the original `a-b,c-d` format.
+/// Dropped assignments are omitted from their line.
+pub fn write_roster(
+    mut w: impl Write,
+    data: &[Vec<Interval<u32>>],
+    plan: &[Vec<Edit>],
+) -> std::io::Result<()> {
+    for (line, edits) in data.iter().zip(plan) {
+        let line = line
+            .iter()
+            .zip(edits)
+            .filter_map(|(&x, edit)| edit.apply(x))
+            .join(",");
+        writeln!(w, "{}", line)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use common::rng::XorShift;
+
+    use super::*;
+    use crate::parser::parse_line;
+
+    fn random_roster(rng: &mut XorShift) -> Vec<Vec<Interval<u32>>> {
+        (0..1 + rng.below(4))
+            .map(|_| {
+                (0..1 + rng.below(3))
+                    .map(|_| {
+                        let start = 1 + rng.below(20) as u32;
+                        Interval::new(start, start + rng.below(6) as u32)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Largest number of pairwise disjoint intervals, found by trying every subset.
+    fn max_disjoint(intervals: &[Interval<u32>]) -> usize {
+        intervals
+            .iter()
+            .powerset()
+            .filter(|set| set.iter().tuple_combinations().all(|(a, b)| !a.overlaps(b)))
+            .map(|set| set.len())
+            .max()
+            .unwrap()
+    }
+
+    /// Checks that `edits` change as few of `intervals` as possible, only ever shrinking
+    /// them, and leave no overlaps. Returns the resulting assignments.
+    fn check(intervals: &[Interval<u32>], edits: &[Edit]) -> Vec<Interval<u32>> {
+        let edited = edits.iter().filter(|&&edit| edit != Edit::Keep).count();
+        assert_eq!(
+            edited,
+            intervals.len() - max_disjoint(intervals),
+            "{:?}",
+            intervals
+        );
+        let result = intervals
+            .iter()
+            .zip(edits)
+            .filter_map(|(&x, edit)| edit.apply(x).map(|y| (x, y)))
+            .collect_vec();
+        for &(x, y) in &result {
+            assert!(x.contains_interval(&y), "{} became {}", x, y);
+        }
+        for ((_, a), (_, b)) in result.iter().tuple_combinations() {
+            assert!(!a.overlaps(b), "{} and {} overlap in {:?}", a, b, intervals);
+        }
+        result.into_iter().map(|(_, y)| y).collect()
+    }
+
+    #[test]
+    fn plans_match_brute_force() {
+        let mut rng = XorShift::new(2022);
+        for _ in 0..500 {
+            let data = random_roster(&mut rng);
+            for (line, edits) in data.iter().zip(plan_lines(&data)) {
+                check(line, &edits);
+            }
+
+            let plan = plan_roster(&data);
+            let flat = data.iter().flatten().copied().collect_vec();
+            let result = check(&flat, &plan.concat());
+
+            // The written roster parses back to the same assignments, even on lines left
+            // with fewer than two ranges.
+            let mut out = Vec::new();
+            write_roster(&mut out, &data, &plan).unwrap();
+            let reparsed = String::from_utf8(out)
+                .unwrap()
+                .lines()
+                .flat_map(|line| parse_line(line, false).unwrap().assignments)
+                .collect_vec();
+            assert_eq!(reparsed, result);
+        }
+    }
+}
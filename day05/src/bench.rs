@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use common::rng::XorShift;
+
+use crate::crane::{Crane, CrateMover9000, CrateMover9001};
+use crate::instruction::Instruction;
+use crate::{simulate, top_of_stacks, Crate};
+
+/// Generates `num_stacks` stacks holding `num_crates` crates in total,
+/// and `num_instructions` valid instructions moving up to `max_move` crates each.
+pub fn generate(
+    num_stacks: usize,
+    num_crates: usize,
+    num_instructions: usize,
+    max_move: usize,
+    seed: u64,
+) -> (Vec<VecDeque<Crate>>, Vec<Instruction>) {
+    let mut rng = XorShift::new(seed);
+    let mut stacks = vec![VecDeque::new(); num_stacks];
+    for _ in 0..num_crates {
+        let name = ((b'A' + rng.below(26) as u8) as char).to_string();
+        stacks[rng.below(num_stacks)].push_back(name);
+    }
+
+    let mut heights = stacks.iter().map(|s| s.len()).collect::<Vec<_>>();
+    let mut instructions = Vec::with_capacity(num_instructions);
+    while instructions.len() < num_instructions {
+        let from = rng.below(num_stacks);
+        let to = rng.below(num_stacks);
+        if from == to || heights[from] == 0 {
+            continue;
+        }
+        let n = 1 + rng.below(heights[from].min(max_move));
+        heights[from] -= n;
+        heights[to] += n;
+        instructions.push(Instruction {
+            n,
+            from: from + 1,
+            to: to + 1,
+        });
+    }
+    (stacks, instructions)
+}
+
+/// Reference CrateMover 9000, moving crates one `pop_back`/`push_back` at a time.
+struct NaiveCrateMover9000;
+
+impl Crane for NaiveCrateMover9000 {
+    fn name(&self) -> String {
+        "naive CrateMover 9000".to_string()
+    }
+
+    fn apply(&mut self, from: &mut VecDeque<Crate>, to: &mut VecDeque<Crate>, n: usize) {
+        for _ in 0..n {
+            let elem = from.pop_back().unwrap();
+            to.push_back(elem);
+        }
+    }
+
+    fn revert(&mut self, from: &mut VecDeque<Crate>, to: &mut VecDeque<Crate>, n: usize) {
+        self.apply(to, from, n);
+    }
+}
+
+/// Reference CrateMover 9001, moving crates through a temporary `VecDeque`.
+struct NaiveCrateMover9001;
+
+impl Crane for NaiveCrateMover9001 {
+    fn name(&self) -> String {
+        "naive CrateMover 9001".to_string()
+    }
+
+    fn apply(&mut self, from: &mut VecDeque<Crate>, to: &mut VecDeque<Crate>, n: usize) {
+        let mut tmp = VecDeque::new();
+        for _ in 0..n {
+            let elem = from.pop_back().unwrap();
+            tmp.push_back(elem);
+        }
+        while let Some(elem) = tmp.pop_back() {
+            to.push_back(elem);
+        }
+    }
+
+    fn revert(&mut self, from: &mut VecDeque<Crate>, to: &mut VecDeque<Crate>, n: usize) {
+        self.apply(to, from, n);
+    }
+}
+
+fn measure(
+    stacks: &[VecDeque<Crate>],
+    instructions: &[Instruction],
+    crane: &mut dyn Crane,
+) -> Vec<VecDeque<Crate>> {
+    let start = Instant::now();
+    let state = simulate(stacks, instructions, crane, false);
+    let elapsed = start.elapsed();
+    let top = top_of_stacks(&state, "_", "");
+    println!("{:>24}: {} in {:?}", crane.name(), top, elapsed);
+    state
+}
+
+pub fn run(num_crates: usize, num_instructions: usize) {
+    println!(
+        "==> Generating {} crates and {} instructions...",
+        num_crates, num_instructions
+    );
+    let (stacks, instructions) = generate(9, num_crates, num_instructions, 1000, 42);
+    let moved: usize = instructions.iter().map(|i| i.n).sum();
+    println!("Total crates moved: {}", moved);
+
+    println!("==> Benchmarking...");
+    let a = measure(&stacks, &instructions, &mut NaiveCrateMover9000);
+    let b = measure(&stacks, &instructions, &mut CrateMover9000);
+    assert_eq!(a, b);
+    let a = measure(&stacks, &instructions, &mut NaiveCrateMover9001);
+    let b = measure(&stacks, &instructions, &mut CrateMover9001);
+    assert_eq!(a, b);
+}
@@ -0,0 +1,164 @@
+use std::collections::VecDeque;
+
+use crate::Crate;
+
+pub trait Crane {
+    fn name(&self) -> String;
+
+    /// Moves `n` crates from the top of `from` to the top of `to`.
+    ///
+    /// Implementations should move the whole block at once (e.g. via `drain` + `extend`),
+    /// so that a move costs a single bulk copy instead of `n` separate pops and pushes.
+    fn apply(&mut self, from: &mut VecDeque<Crate>, to: &mut VecDeque<Crate>, n: usize);
+
+    /// Undoes the latest `apply(from, to, n)`, putting the `n` crates on top of `to`
+    /// back onto `from` in their original order.
+    ///
+    /// Moves are undone in reverse order, so stateful cranes may simply roll their state back.
+    fn revert(&mut self, from: &mut VecDeque<Crate>, to: &mut VecDeque<Crate>, n: usize);
+}
+
+/// Moves crates one at a time, reversing their order.
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn name(&self) -> String {
+        "CrateMover 9000".to_string()
+    }
+
+    fn apply(&mut self, from: &mut VecDeque<Crate>, to: &mut VecDeque<Crate>, n: usize) {
+        let start = from.len() - n;
+        to.extend(from.drain(start..).rev());
+    }
+
+    fn revert(&mut self, from: &mut VecDeque<Crate>, to: &mut VecDeque<Crate>, n: usize) {
+        self.apply(to, from, n);
+    }
+}
+
+/// Moves all crates at once, keeping their order.
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn name(&self) -> String {
+        "CrateMover 9001".to_string()
+    }
+
+    fn apply(&mut self, from: &mut VecDeque<Crate>, to: &mut VecDeque<Crate>, n: usize) {
+        let start = from.len() - n;
+        to.extend(from.drain(start..));
+    }
+
+    fn revert(&mut self, from: &mut VecDeque<Crate>, to: &mut VecDeque<Crate>, n: usize) {
+        self.apply(to, from, n);
+    }
+}
+
+/// Lifts at most `max_lift` crates at a time, keeping their order within each lift.
+pub struct MaxLift {
+    pub max_lift: usize,
+}
+
+impl Crane for MaxLift {
+    fn name(&self) -> String {
+        format!("max-lift crane ({} crates)", self.max_lift)
+    }
+
+    fn apply(&mut self, from: &mut VecDeque<Crate>, to: &mut VecDeque<Crate>, n: usize) {
+        let mut remaining = n;
+        while remaining > 0 {
+            let k = remaining.min(self.max_lift);
+            CrateMover9001.apply(from, to, k);
+            remaining -= k;
+        }
+    }
+
+    fn revert(&mut self, from: &mut VecDeque<Crate>, to: &mut VecDeque<Crate>, n: usize) {
+        // The last lift (the bottom of the block) ended up on top, so lift it back first.
+        if n == 0 {
+            return;
+        }
+        let last = n - (n - 1) / self.max_lift * self.max_lift;
+        CrateMover9001.apply(to, from, last);
+        let mut remaining = n - last;
+        while remaining > 0 {
+            CrateMover9001.apply(to, from, self.max_lift);
+            remaining -= self.max_lift;
+        }
+    }
+}
+
+/// Keeps the order of the moved block, except that every other crate in it is reversed:
+/// the top crate, the third one and so on swap ends among themselves, while the crates
+/// in between stay in place.
+pub struct Alternating;
+
+impl Crane for Alternating {
+    fn name(&self) -> String {
+        "alternating crane".to_string()
+    }
+
+    fn apply(&mut self, from: &mut VecDeque<Crate>, to: &mut VecDeque<Crate>, n: usize) {
+        let start = from.len() - n;
+        to.extend(from.drain(start..));
+        // Positions of every other crate of the block, from the top of `to` down.
+        let mut top = to.len();
+        let mut bottom = to.len() - n + (n + 1) % 2;
+        while bottom + 1 < top {
+            to.swap(top - 1, bottom);
+            top -= 2;
+            bottom += 2;
+        }
+    }
+
+    fn revert(&mut self, from: &mut VecDeque<Crate>, to: &mut VecDeque<Crate>, n: usize) {
+        // Reversing the same crates again restores them.
+        self.apply(to, from, n);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn revert_restores_stacks() {
+        let cranes: Vec<Box<dyn Crane>> = vec![
+            Box::new(CrateMover9000),
+            Box::new(CrateMover9001),
+            Box::new(MaxLift { max_lift: 3 }),
+            Box::new(MaxLift { max_lift: 4 }),
+            Box::new(Alternating),
+        ];
+        for mut crane in cranes {
+            for n in 0..=8 {
+                let mut from: VecDeque<Crate> = "ABCDEFGH".chars().map(String::from).collect();
+                let mut to: VecDeque<Crate> = ["X".to_string()].into();
+                let (from0, to0) = (from.clone(), to.clone());
+                crane.apply(&mut from, &mut to, n);
+                crane.apply(&mut to, &mut from, 1);
+                crane.revert(&mut to, &mut from, 1);
+                crane.revert(&mut from, &mut to, n);
+                assert_eq!(
+                    (&from, &to),
+                    (&from0, &to0),
+                    "{} moving {}",
+                    crane.name(),
+                    n
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn alternating_reverses_every_other_crate() {
+        for (n, expected) in [(0, ""), (1, "E"), (2, "DE"), (4, "BEDC"), (5, "EBCDA")] {
+            let mut from: VecDeque<Crate> = "ABCDE".chars().map(String::from).collect();
+            let mut to = VecDeque::new();
+            Alternating.apply(&mut from, &mut to, n);
+            assert_eq!(to.iter().join(""), expected, "moving {}", n);
+        }
+    }
+}
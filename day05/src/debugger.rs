@@ -0,0 +1,90 @@
+use std::io::{self, BufRead, Write};
+
+use crate::drawing::render_stacks;
+use crate::simulation::Simulation;
+use crate::top_of_stacks;
+
+const HELP: &str = "\
+Commands:
+  n [K]     step forward K instructions (default 1)
+  p [K]     step back K instructions (default 1)
+  goto N    jump to the state after instruction N (0 is the initial state)
+  show      print the stacks
+  top       print the top crate of each stack
+  help      print this message
+  q         quit";
+
+fn print_position(sim: &Simulation) {
+    print!(
+        "At instruction {}/{}",
+        sim.position(),
+        sim.num_instructions()
+    );
+    match sim.next_instruction() {
+        Some(instruction) => println!(", next: {}", instruction),
+        None => println!(", no instructions left"),
+    }
+}
+
+fn parse_count(arg: Option<&str>) -> Result<usize, String> {
+    match arg {
+        None => Ok(1),
+        Some(s) => s.parse().map_err(|e| format!("Bad count {:?}: {}", s, e)),
+    }
+}
+
+/// Runs the interactive debugger on stdin until `q` or end of input.
+pub fn run(sim: &mut Simulation, placeholder: &str, separator: &str) -> io::Result<()> {
+    println!("{}", HELP);
+    print_position(sim);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+        let Some(line) = lines.next() else {
+            println!();
+            break;
+        };
+        let line = line?;
+        let mut words = line.split_whitespace();
+        let result = match (words.next(), words.next()) {
+            (None, _) => continue,
+            (Some("n"), arg) => {
+                parse_count(arg).map(|k| sim.goto(sim.position().saturating_add(k)))
+            }
+            (Some("p"), arg) => {
+                parse_count(arg).map(|k| sim.goto(sim.position().saturating_sub(k)))
+            }
+            (Some("goto"), Some(arg)) => arg
+                .parse()
+                .map(|position| sim.goto(position))
+                .map_err(|e| format!("Bad instruction number {:?}: {}", arg, e)),
+            (Some("goto"), None) => Err("Usage: goto N".to_string()),
+            (Some("show"), _) => {
+                for line in render_stacks(sim.state()) {
+                    println!("{}", line);
+                }
+                continue;
+            }
+            (Some("top"), _) => {
+                println!(
+                    "Top of stacks: {}",
+                    top_of_stacks(sim.state(), placeholder, separator)
+                );
+                continue;
+            }
+            (Some("help"), _) => {
+                println!("{}", HELP);
+                continue;
+            }
+            (Some("q" | "quit"), _) => break,
+            (Some(command), _) => Err(format!("Unknown command {:?}, try 'help'", command)),
+        };
+        match result {
+            Ok(()) => print_position(sim),
+            Err(e) => println!("{}", e),
+        }
+    }
+    Ok(())
+}
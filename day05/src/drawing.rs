@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+
+use crate::Crate;
+
+/// Whitespace-separated tokens of `line`, with their (inclusive) character spans.
+fn tokens(line: &[char]) -> Vec<(usize, usize, String)> {
+    let mut result = Vec::new();
+    let mut i = 0;
+    while i < line.len() {
+        if line[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < line.len() && !line[i].is_whitespace() {
+            i += 1;
+        }
+        result.push((start, i - 1, line[start..i].iter().collect()));
+    }
+    result
+}
+
+/// Parses the drawing of the stacks, including the trailing row of stack numbers.
+///
+/// Stack columns are located by the positions of the numbers in the label row,
+/// and each crate `[...]` goes to the stack whose label lies under it. This allows
+/// crates with multi-character names and more than nine stacks.
+pub fn parse_stacks(lines: &[String]) -> Result<Vec<VecDeque<Crate>>, String> {
+    let (label_row, rows) = lines.split_last().ok_or("Empty drawing")?;
+    let labels = tokens(&label_row.chars().collect::<Vec<_>>());
+    for (i, (_, _, label)) in labels.iter().enumerate() {
+        if *label != (i + 1).to_string() {
+            return Err(format!("Expected stack label {}, found {:?}", i + 1, label));
+        }
+    }
+
+    let mut stacks = vec![VecDeque::new(); labels.len()];
+    for (row, line) in rows.iter().enumerate().rev() {
+        let line = line.chars().collect::<Vec<_>>();
+        let mut i = 0;
+        while i < line.len() {
+            if line[i].is_whitespace() {
+                i += 1;
+                continue;
+            }
+            if line[i] != '[' {
+                return Err(format!(
+                    "Unexpected {:?} at row {}, column {}",
+                    line[i],
+                    row + 1,
+                    i + 1
+                ));
+            }
+            let open = i;
+            let close = (open..line.len())
+                .find(|&j| line[j] == ']')
+                .ok_or_else(|| format!("Unclosed crate at row {}, column {}", row + 1, open + 1))?;
+            let k = labels
+                .iter()
+                .position(|&(start, end, _)| start <= close && open <= end)
+                .ok_or_else(|| {
+                    format!(
+                        "Crate at row {}, column {} is not above any stack label",
+                        row + 1,
+                        open + 1
+                    )
+                })?;
+            stacks[k].push_back(line[open + 1..close].iter().collect());
+            i = close + 1;
+        }
+    }
+    Ok(stacks)
+}
+
+/// Pads `s` with spaces on both sides to `width` characters.
+fn center(s: &str, width: usize) -> String {
+    let len = s.chars().count();
+    let left = (width - len) / 2;
+    format!(
+        "{}{}{}",
+        " ".repeat(left),
+        s,
+        " ".repeat(width - len - left)
+    )
+}
+
+/// Renders the stacks in the drawing format understood by [`parse_stacks`].
+pub fn render_stacks(stacks: &[VecDeque<Crate>]) -> Vec<String> {
+    let widths = stacks
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let label = (i + 1).to_string().len();
+            let crates = s.iter().map(|c| c.chars().count() + 2).max().unwrap_or(0);
+            label.max(crates).max(3)
+        })
+        .collect::<Vec<_>>();
+    let height = stacks.iter().map(|s| s.len()).max().unwrap_or(0);
+    let mut lines = Vec::with_capacity(height + 1);
+    for level in (0..height).rev() {
+        let line = stacks
+            .iter()
+            .zip(&widths)
+            .map(|(s, &width)| match s.get(level) {
+                Some(c) => center(&format!("[{}]", c), width),
+                None => " ".repeat(width),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        lines.push(line.trim_end().to_string());
+    }
+    let numbers = widths
+        .iter()
+        .enumerate()
+        .map(|(i, &width)| center(&(i + 1).to_string(), width))
+        .collect::<Vec<_>>()
+        .join(" ");
+    lines.push(numbers.trim_end().to_string());
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(s: &str) -> Vec<String> {
+        s.lines().map(|line| line.to_string()).collect()
+    }
+
+    fn stack(crates: &[&str]) -> VecDeque<Crate> {
+        crates.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let drawing = lines("    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3");
+        let stacks = parse_stacks(&drawing).unwrap();
+        assert_eq!(stacks[1], stack(&["M", "C", "D"]));
+        assert_eq!(render_stacks(&stacks), drawing);
+        assert_eq!(parse_stacks(&render_stacks(&stacks)).unwrap(), stacks);
+    }
+
+    #[test]
+    fn round_trip_with_empty_stacks() {
+        let stacks = vec![stack(&[]), stack(&["A", "B"]), stack(&[])];
+        let drawing = render_stacks(&stacks);
+        assert_eq!(drawing, lines("    [B]\n    [A]\n 1   2   3"));
+        assert_eq!(parse_stacks(&drawing).unwrap(), stacks);
+    }
+
+    #[test]
+    fn wide_layout() {
+        let mut stacks = vec![stack(&["X"]); 11];
+        stacks[0] = stack(&["AB", "C"]);
+        stacks[10] = stack(&["LONG"]);
+        let drawing = render_stacks(&stacks);
+        assert_eq!(parse_stacks(&drawing).unwrap(), stacks);
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use itertools::Itertools;
+use once_cell_regex::regex;
+
+use crate::Crate;
+
+/// Instruction `move {n} from {from} to {to}`, with 1-based stack numbers.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Instruction {
+    pub n: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "move {} from {} to {}", self.n, self.from, self.to)
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let re = regex!(r"^move (\d+) from (\d+) to (\d+)$");
+        let captures = re
+            .captures(s)
+            .ok_or_else(|| format!("Bad instruction: {:?}", s))?;
+        let parse = |i: usize| {
+            captures[i]
+                .parse::<usize>()
+                .map_err(|e| format!("Bad number {:?}: {}", &captures[i], e))
+        };
+        Ok(Instruction {
+            n: parse(1)?,
+            from: parse(2)?,
+            to: parse(3)?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidMove {
+    /// 0-based index of the failing instruction.
+    pub index: usize,
+    pub instruction: Instruction,
+    /// Stack heights right before the failing instruction.
+    pub heights: Vec<usize>,
+    pub reason: String,
+}
+
+impl Display for InvalidMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Instruction {} ({:?}) is invalid: {}. Stack heights: [{}]",
+            self.index + 1,
+            self.instruction.to_string(),
+            self.reason,
+            self.heights.iter().join(", ")
+        )
+    }
+}
+
+impl std::error::Error for InvalidMove {}
+
+/// Checks that every instruction refers to existing stacks and never moves
+/// more crates than the source stack holds. Only stack heights are tracked,
+/// so the result does not depend on the crane model.
+pub fn validate(
+    stacks: &[VecDeque<Crate>],
+    instructions: &[Instruction],
+) -> Result<(), InvalidMove> {
+    let mut heights = stacks.iter().map(|s| s.len()).collect_vec();
+    for (index, &instruction) in instructions.iter().enumerate() {
+        let Instruction { n, from, to } = instruction;
+        let reason = if !(1..=heights.len()).contains(&from) {
+            Some(format!(
+                "source stack {} does not exist (there are {} stacks)",
+                from,
+                heights.len()
+            ))
+        } else if !(1..=heights.len()).contains(&to) {
+            Some(format!(
+                "target stack {} does not exist (there are {} stacks)",
+                to,
+                heights.len()
+            ))
+        } else if heights[from - 1] < n {
+            Some(format!(
+                "cannot move {} crates from stack {} holding only {}",
+                n,
+                from,
+                heights[from - 1]
+            ))
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            return Err(InvalidMove {
+                index,
+                instruction,
+                heights,
+                reason,
+            });
+        }
+        heights[from - 1] -= n;
+        heights[to - 1] += n;
+    }
+    Ok(())
+}
@@ -3,9 +3,21 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use color_eyre::eyre::{bail, eyre};
 use itertools::Itertools;
-use once_cell_regex::regex;
+
+use crate::crane::{Alternating, Crane, CrateMover9000, CrateMover9001, MaxLift};
+use crate::drawing::{parse_stacks, render_stacks};
+use crate::instruction::{validate, Instruction};
+use crate::simulation::Simulation;
+
+mod bench;
+mod crane;
+mod debugger;
+mod drawing;
+mod instruction;
+mod simulation;
 
 #[derive(Parser, Debug)]
 #[command(author, version)]
@@ -14,40 +26,92 @@ struct Cli {
     #[arg(value_name = "FILE")]
     #[arg(default_value = "data/sample.txt")]
     input: PathBuf,
+
+    /// Simulate only the given crane model
+    #[arg(long, value_name = "MODEL")]
+    crane: Option<CraneModel>,
+
+    /// Maximum number of crates lifted at once by the max-lift crane
+    #[arg(long, default_value_t = 3)]
+    #[arg(value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    max_lift: usize,
+
+    /// Print the drawing of the stacks after each instruction
+    #[arg(long)]
+    show_steps: bool,
+
+    /// Step through the instructions interactively (requires --crane)
+    #[arg(long, requires = "crane")]
+    debug: bool,
+
+    /// Crate name shown for empty stacks in the answer
+    #[arg(long, default_value = "_")]
+    placeholder: String,
+
+    /// Separator between the top crates in the answer
+    #[arg(long, default_value = "")]
+    separator: String,
+
+    /// Benchmark against per-crate moves on CRATES generated crates and INSTRUCTIONS instructions
+    #[arg(long, num_args = 2, value_names = ["CRATES", "INSTRUCTIONS"])]
+    bench: Option<Vec<usize>>,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug)]
+enum CraneModel {
+    #[value(name = "9000")]
+    CrateMover9000,
+    #[value(name = "9001")]
+    CrateMover9001,
+    MaxLift,
+    Alternating,
+}
+
+impl CraneModel {
+    fn build(self, max_lift: usize) -> Box<dyn Crane> {
+        match self {
+            CraneModel::CrateMover9000 => Box::new(CrateMover9000),
+            CraneModel::CrateMover9001 => Box::new(CrateMover9001),
+            CraneModel::MaxLift => Box::new(MaxLift { max_lift }),
+            CraneModel::Alternating => Box::new(Alternating),
+        }
+    }
 }
 
-fn parse_stacks(lines: &[String]) -> Vec<VecDeque<char>> {
-    let n = lines
-        .last()
-        .unwrap()
-        .split_whitespace()
-        .last()
-        .unwrap()
-        .parse::<usize>()
-        .unwrap();
-    let mut stacks = vec![VecDeque::new(); n];
-    for j in (0..lines.len() - 1).rev() {
-        let line = &lines[j];
-        for i in 0..n {
-            let pos = 1 + 4 * i;
-            if pos < line.len() {
-                let c = line.as_bytes()[pos] as char;
-                if c != ' ' {
-                    stacks[i].push_back(c);
-                }
+type Crate = String;
+
+/// Runs all instructions with the given crane, starting from `stacks`.
+fn simulate(
+    stacks: &[VecDeque<Crate>],
+    instructions: &[Instruction],
+    crane: &mut dyn Crane,
+    show_steps: bool,
+) -> Vec<VecDeque<Crate>> {
+    let mut sim = Simulation::new(stacks, instructions, crane);
+    if show_steps {
+        println!("Initial state:");
+        for line in render_stacks(sim.state()) {
+            println!("{}", line);
+        }
+    }
+    while let Some(instruction) = sim.next_instruction() {
+        sim.step_forward();
+        if show_steps {
+            println!("After instruction {}: {}", sim.position(), instruction);
+            for line in render_stacks(sim.state()) {
+                println!("{}", line);
             }
         }
     }
-    stacks
+    sim.into_state()
 }
 
-fn parse_instruction(line: &str) -> (usize, usize, usize) {
-    let re = regex!(r"^move (\d+) from (\d+) to (\d+)$");
-    let captures = re.captures(line).unwrap();
-    let n = captures[1].parse::<usize>().unwrap();
-    let from = captures[2].parse::<usize>().unwrap();
-    let to = captures[3].parse::<usize>().unwrap();
-    (n, from, to)
+/// Top crate of each stack, with `placeholder` standing for empty stacks.
+fn top_of_stacks(state: &[VecDeque<Crate>], placeholder: &str, separator: &str) -> String {
+    state
+        .iter()
+        .map(|x| x.back().map_or(placeholder, |c| c.as_str()))
+        .join(separator)
 }
 
 fn main() -> color_eyre::Result<()> {
@@ -56,46 +120,65 @@ fn main() -> color_eyre::Result<()> {
     let args = Cli::parse();
     println!("args = {:?}", args);
 
+    if let Some(bench) = &args.bench {
+        bench::run(bench[0], bench[1]);
+        return Ok(());
+    }
+
     let lines = BufReader::new(File::open(args.input)?)
         .lines()
-        .filter_map(|x| x.ok())
+        .map_while(Result::ok)
         .collect_vec();
 
     let i = lines.iter().position(|line| line.is_empty()).unwrap();
-    let stacks = parse_stacks(&lines[..i]);
-    let instructions = lines[i + 1..]
-        .iter()
-        .map(|line| parse_instruction(line.as_str()))
-        .collect_vec();
-
-    println!("==> Solving part one...");
-    {
-        let mut state = stacks.clone();
-        for &(n, from, to) in &instructions {
-            for _ in 0..n {
-                let elem = state[from - 1].pop_back().unwrap();
-                state[to - 1].push_back(elem);
-            }
+    let stacks = parse_stacks(&lines[..i]).map_err(|e| eyre!(e))?;
+    let mut instructions = Vec::new();
+    let mut errors = Vec::new();
+    for (j, line) in lines.iter().enumerate().skip(i + 1) {
+        match line.parse::<Instruction>() {
+            Ok(instruction) => instructions.push(instruction),
+            Err(e) => errors.push(format!("Line {}: {}", j + 1, e)),
+        }
+    }
+    if !errors.is_empty() {
+        for error in &errors {
+            println!("{}", error);
         }
-        let s: String = state.iter().map(|x| x.back().unwrap()).collect();
-        println!("Top of stacks: {}", s);
+        bail!("Found {} malformed instruction(s)", errors.len());
     }
+    validate(&stacks, &instructions)?;
 
-    println!("==> Solving part two...");
-    {
-        let mut state = stacks.clone();
-        for &(n, from, to) in &instructions {
-            let mut tmp = VecDeque::new();
-            for _ in 0..n {
-                let elem = state[from - 1].pop_back().unwrap();
-                tmp.push_back(elem);
-            }
-            while let Some(elem) = tmp.pop_back() {
-                state[to - 1].push_back(elem);
+    match args.crane {
+        Some(model) => {
+            let mut crane = model.build(args.max_lift);
+            if args.debug {
+                println!("==> Debugging {}...", crane.name());
+                let mut sim = Simulation::new(&stacks, &instructions, crane.as_mut());
+                debugger::run(&mut sim, &args.placeholder, &args.separator)?;
+                return Ok(());
             }
+            println!("==> Simulating {}...", crane.name());
+            let state = simulate(&stacks, &instructions, crane.as_mut(), args.show_steps);
+            println!(
+                "Top of stacks: {}",
+                top_of_stacks(&state, &args.placeholder, &args.separator)
+            );
+        }
+        None => {
+            println!("==> Solving part one...");
+            let state = simulate(&stacks, &instructions, &mut CrateMover9000, args.show_steps);
+            println!(
+                "Top of stacks: {}",
+                top_of_stacks(&state, &args.placeholder, &args.separator)
+            );
+
+            println!("==> Solving part two...");
+            let state = simulate(&stacks, &instructions, &mut CrateMover9001, args.show_steps);
+            println!(
+                "Top of stacks: {}",
+                top_of_stacks(&state, &args.placeholder, &args.separator)
+            );
         }
-        let s: String = state.iter().map(|x| x.back().unwrap()).collect();
-        println!("Top of stacks: {}", s);
     }
 
     Ok(())
@@ -0,0 +1,129 @@
+use std::collections::VecDeque;
+
+use crate::crane::Crane;
+use crate::instruction::Instruction;
+use crate::Crate;
+
+/// Simulation that can step forwards and backwards through the instructions.
+///
+/// Instructions are expected to be validated beforehand. Each instruction is its
+/// own undo record: [`Crane::revert`] only needs to know which stacks and how many
+/// crates were involved, so stepping back costs no more than stepping forward.
+pub struct Simulation<'a> {
+    state: Vec<VecDeque<Crate>>,
+    instructions: &'a [Instruction],
+    crane: &'a mut dyn Crane,
+    /// Number of instructions applied so far.
+    position: usize,
+}
+
+impl<'a> Simulation<'a> {
+    pub fn new(
+        stacks: &[VecDeque<Crate>],
+        instructions: &'a [Instruction],
+        crane: &'a mut dyn Crane,
+    ) -> Self {
+        Simulation {
+            state: stacks.to_vec(),
+            instructions,
+            crane,
+            position: 0,
+        }
+    }
+
+    pub fn state(&self) -> &[VecDeque<Crate>] {
+        &self.state
+    }
+
+    pub fn into_state(self) -> Vec<VecDeque<Crate>> {
+        self.state
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn num_instructions(&self) -> usize {
+        self.instructions.len()
+    }
+
+    /// The instruction that the next [`step_forward`](Self::step_forward) applies.
+    pub fn next_instruction(&self) -> Option<Instruction> {
+        self.instructions.get(self.position).copied()
+    }
+
+    /// Applies the next instruction. Returns `false` if all instructions are applied.
+    pub fn step_forward(&mut self) -> bool {
+        let Some(Instruction { n, from, to }) = self.next_instruction() else {
+            return false;
+        };
+        if from != to {
+            let (src, dst) = two_mut(&mut self.state, from - 1, to - 1);
+            self.crane.apply(src, dst, n);
+        }
+        self.position += 1;
+        true
+    }
+
+    /// Undoes the last applied instruction. Returns `false` if at the initial state.
+    pub fn step_back(&mut self) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+        self.position -= 1;
+        let Instruction { n, from, to } = self.instructions[self.position];
+        if from != to {
+            let (src, dst) = two_mut(&mut self.state, from - 1, to - 1);
+            self.crane.revert(src, dst, n);
+        }
+        true
+    }
+
+    /// Steps forwards or backwards until exactly `position` instructions are applied
+    /// (or as close as possible).
+    pub fn goto(&mut self, position: usize) {
+        while self.position < position && self.step_forward() {}
+        while self.position > position && self.step_back() {}
+    }
+}
+
+/// Returns mutable references to two distinct elements of the slice.
+fn two_mut<T>(xs: &mut [T], i: usize, j: usize) -> (&mut T, &mut T) {
+    assert_ne!(i, j);
+    if i < j {
+        let (left, right) = xs.split_at_mut(j);
+        (&mut left[i], &mut right[0])
+    } else {
+        let (left, right) = xs.split_at_mut(i);
+        (&mut right[0], &mut left[j])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::generate;
+    use crate::crane::{Alternating, MaxLift};
+
+    #[test]
+    fn goto_is_reversible() {
+        let (stacks, instructions) = generate(5, 100, 500, 20, 7);
+        let mut crane = Alternating;
+        let mut sim = Simulation::new(&stacks, &instructions, &mut crane);
+        sim.goto(437);
+        let snapshot = sim.state().to_vec();
+        sim.goto(500);
+        sim.goto(434);
+        sim.goto(437);
+        assert_eq!(sim.state(), snapshot);
+        sim.goto(0);
+        assert_eq!(sim.state(), stacks);
+
+        let mut crane = MaxLift { max_lift: 3 };
+        let mut sim = Simulation::new(&stacks, &instructions, &mut crane);
+        sim.goto(usize::MAX);
+        assert_eq!(sim.position(), instructions.len());
+        sim.goto(0);
+        assert_eq!(sim.state(), stacks);
+    }
+}
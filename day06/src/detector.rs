@@ -0,0 +1,443 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::io::{self, Read};
+use std::ops::ControlFlow;
+
+use clap::ValueEnum;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Sliding window over the last `k` symbols, tracking how many of them are distinct.
+///
+/// Symbols are small integers (bytes, or ids of interned characters), used directly
+/// as indices into a table of counts.
+pub struct Detector {
+    k: usize,
+    window: VecDeque<usize>,
+    counts: Vec<u32>,
+    distinct: usize,
+}
+
+impl Detector {
+    pub fn new(k: usize) -> Self {
+        Detector {
+            k,
+            window: VecDeque::with_capacity(k),
+            counts: vec![0; 256],
+            distinct: 0,
+        }
+    }
+
+    pub fn window_size(&self) -> usize {
+        self.k
+    }
+
+    /// Pushes the next symbol. Returns `true` if the last `k` symbols are now pairwise distinct.
+    pub fn push(&mut self, symbol: usize) -> bool {
+        if self.window.len() == self.k {
+            let old = self.window.pop_front().unwrap();
+            self.counts[old] -= 1;
+            if self.counts[old] == 0 {
+                self.distinct -= 1;
+            }
+        }
+        if symbol >= self.counts.len() {
+            self.counts.resize(symbol + 1, 0);
+        }
+        self.window.push_back(symbol);
+        self.counts[symbol] += 1;
+        if self.counts[symbol] == 1 {
+            self.distinct += 1;
+        }
+        self.distinct == self.k
+    }
+
+    /// Forgets all symbols seen so far, so the next marker cannot overlap the previous ones.
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.counts.fill(0);
+        self.distinct = 0;
+    }
+}
+
+/// Marker of size `window` completed right after symbol number `position` (1-based).
+///
+/// When scanning text, `position` counts units of the trimmed and normalized text, while
+/// the offsets always refer to the original input.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Marker {
+    pub window: usize,
+    pub position: u64,
+    /// Number of characters in the input up to the end of the marker, when scanning text.
+    pub char_offset: Option<u64>,
+    /// Number of bytes in the input up to the end of the marker.
+    pub byte_offset: u64,
+}
+
+impl Display for Marker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.position)?;
+        if let Some(chars) = self.char_offset {
+            write!(f, " (input char {}, byte {})", chars, self.byte_offset)?;
+        }
+        Ok(())
+    }
+}
+
+/// Unit of text in which markers are looked for.
+#[derive(ValueEnum, Copy, Clone, Debug)]
+pub enum Unit {
+    Chars,
+    Graphemes,
+}
+
+/// Feeds grapheme clusters of the input to the detectors, trimming surrounding whitespace
+/// and bringing every cluster to NFC on the way.
+struct TextScanner<F> {
+    unit: Unit,
+    detectors: Vec<Detector>,
+    non_overlapping: bool,
+    /// Symbol ids of the distinct units seen so far.
+    ids: HashMap<String, usize>,
+    position: u64,
+    /// Whether a non-whitespace cluster was seen, so whitespace is no longer leading.
+    started: bool,
+    /// Whitespace clusters held back until we know they are not trailing, with their offsets.
+    pending: Vec<(String, u64, u64)>,
+    on_marker: F,
+}
+
+impl<F: FnMut(Marker) -> ControlFlow<()>> TextScanner<F> {
+    /// Takes the next cluster, starting at the given character and byte offsets of the input.
+    fn cluster(&mut self, text: &str, chars: u64, bytes: u64) -> ControlFlow<()> {
+        if text.chars().all(char::is_whitespace) {
+            if self.started {
+                self.pending.push((text.to_string(), chars, bytes));
+            }
+            return ControlFlow::Continue(());
+        }
+        self.started = true;
+        for (text, chars, bytes) in std::mem::take(&mut self.pending) {
+            self.normalized(&text, chars, bytes)?;
+        }
+        self.normalized(text, chars, bytes)
+    }
+
+    /// Feeds the cluster in NFC. Canonical composition never crosses cluster boundaries,
+    /// so normalizing cluster by cluster normalizes the whole text. Characters of a cluster
+    /// changed by the normalization are all reported at the end of the cluster.
+    fn normalized(&mut self, text: &str, chars: u64, bytes: u64) -> ControlFlow<()> {
+        let end = (
+            chars + text.chars().count() as u64,
+            bytes + text.len() as u64,
+        );
+        let normalized = text.nfc().collect::<String>();
+        match self.unit {
+            Unit::Graphemes => self.push(&normalized, end),
+            Unit::Chars if normalized == text => {
+                for (i, (j, c)) in text.char_indices().enumerate() {
+                    let offsets = (chars + i as u64 + 1, bytes + (j + c.len_utf8()) as u64);
+                    self.push(&text[j..j + c.len_utf8()], offsets)?;
+                }
+                ControlFlow::Continue(())
+            }
+            Unit::Chars => {
+                for c in normalized.chars() {
+                    self.push(c.encode_utf8(&mut [0; 4]), end)?;
+                }
+                ControlFlow::Continue(())
+            }
+        }
+    }
+
+    /// Feeds a single unit ending at the given character and byte offsets of the input.
+    fn push(&mut self, unit: &str, (chars, bytes): (u64, u64)) -> ControlFlow<()> {
+        let next_id = self.ids.len();
+        let id = *self.ids.entry(unit.to_string()).or_insert(next_id);
+        self.position += 1;
+        for detector in &mut self.detectors {
+            if detector.push(id) {
+                if self.non_overlapping {
+                    detector.reset();
+                }
+                let marker = Marker {
+                    window: detector.window_size(),
+                    position: self.position,
+                    char_offset: Some(chars),
+                    byte_offset: bytes,
+                };
+                (self.on_marker)(marker)?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Same as [`scan`], but over the characters or grapheme clusters of the UTF-8 text read
+/// from `reader`. The text is decoded chunk by chunk, with surrounding whitespace (such
+/// as the trailing newline) trimmed and every cluster brought to NFC, so that precomposed
+/// and decomposed forms of a character compare equal. Each distinct unit is interned into
+/// a symbol id. Returns the number of units consumed.
+pub fn scan_text<R: Read>(
+    mut reader: R,
+    unit: Unit,
+    windows: &[usize],
+    non_overlapping: bool,
+    on_marker: impl FnMut(Marker) -> ControlFlow<()>,
+) -> io::Result<u64> {
+    let mut scanner = TextScanner {
+        unit,
+        detectors: windows.iter().map(|&k| Detector::new(k)).collect(),
+        non_overlapping,
+        ids: HashMap::new(),
+        position: 0,
+        started: false,
+        pending: Vec::new(),
+        on_marker,
+    };
+    let mut buf = vec![0u8; 1 << 16];
+    // Bytes read but not decoded yet, ending with an incomplete UTF-8 sequence.
+    let mut undecoded = Vec::new();
+    // Text decoded but not fed yet, starting at a cluster boundary.
+    let mut text = String::new();
+    // Offsets of the start of `text` in the input.
+    let (mut chars, mut bytes) = (0u64, 0u64);
+    loop {
+        let len = match reader.read(&mut buf) {
+            Ok(len) => len,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        undecoded.extend_from_slice(&buf[..len]);
+        let valid = match std::str::from_utf8(&undecoded) {
+            Ok(s) => s.len(),
+            Err(e) if len > 0 && e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => {
+                let offset = bytes + (text.len() + e.valid_up_to()) as u64;
+                let msg = format!("Invalid UTF-8 at byte {}", offset);
+                return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+            }
+        };
+        text.push_str(std::str::from_utf8(&undecoded[..valid]).unwrap());
+        undecoded.drain(..valid);
+
+        // The last cluster may continue in the next chunk, unless the input is over.
+        let clusters = text.grapheme_indices(true).collect::<Vec<_>>();
+        let complete = if len == 0 {
+            clusters.len()
+        } else {
+            clusters.len().saturating_sub(1)
+        };
+        let mut fed = 0;
+        for &(i, cluster) in &clusters[..complete] {
+            let offset = bytes + i as u64;
+            if scanner.cluster(cluster, chars, offset).is_break() {
+                return Ok(scanner.position);
+            }
+            chars += cluster.chars().count() as u64;
+            fed = i + cluster.len();
+        }
+        bytes += fed as u64;
+        text.drain(..fed);
+        if len == 0 {
+            return Ok(scanner.position);
+        }
+    }
+}
+
+/// Streams `reader` in chunks, feeding each byte to one detector per window size, and
+/// calls `on_marker` for every completed marker until it returns [`ControlFlow::Break`].
+///
+/// With `non_overlapping`, a detector starts afresh after each marker it reports.
+/// Returns the number of bytes consumed.
+pub fn scan<R: Read>(
+    mut reader: R,
+    windows: &[usize],
+    non_overlapping: bool,
+    mut on_marker: impl FnMut(Marker) -> ControlFlow<()>,
+) -> io::Result<u64> {
+    let mut detectors = windows
+        .iter()
+        .map(|&k| Detector::new(k))
+        .collect::<Vec<_>>();
+    let mut buf = vec![0u8; 1 << 16];
+    let mut position = 0u64;
+    loop {
+        let len = match reader.read(&mut buf) {
+            Ok(0) => return Ok(position),
+            Ok(len) => len,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        for &b in &buf[..len] {
+            position += 1;
+            for detector in &mut detectors {
+                if detector.push(b as usize) {
+                    if non_overlapping {
+                        detector.reset();
+                    }
+                    let marker = Marker {
+                        window: detector.window_size(),
+                        position,
+                        char_offset: None,
+                        byte_offset: position,
+                    };
+                    if on_marker(marker).is_break() {
+                        return Ok(position);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_markers(s: &str, windows: &[usize], non_overlapping: bool) -> Vec<Marker> {
+        let mut markers = Vec::new();
+        scan(s.as_bytes(), windows, non_overlapping, |m| {
+            markers.push(m);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        markers
+    }
+
+    fn positions(markers: &[Marker], window: usize) -> Vec<u64> {
+        markers
+            .iter()
+            .filter(|m| m.window == window)
+            .map(|m| m.position)
+            .collect()
+    }
+
+    #[test]
+    fn reports_all_markers() {
+        let markers = all_markers("aabcdeeab", &[3, 4], false);
+        assert_eq!(positions(&markers, 3), vec![4, 5, 6, 9]);
+        assert_eq!(positions(&markers, 4), vec![5, 6]);
+    }
+
+    #[test]
+    fn non_overlapping_markers() {
+        let markers = all_markers("aabcdeeab", &[3, 4], true);
+        assert_eq!(positions(&markers, 3), vec![4, 9]);
+        assert_eq!(positions(&markers, 4), vec![5]);
+    }
+
+    /// Reader handing out a single byte at a time, splitting every multi-byte character.
+    struct ByteByByte<'a>(&'a [u8]);
+
+    impl Read for ByteByByte<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.0.len().min(buf.len()).min(1);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    fn text_markers(s: &str, unit: Unit, k: usize) -> Vec<Marker> {
+        let mut markers = Vec::new();
+        scan_text(s.as_bytes(), unit, &[k], false, |m| {
+            markers.push(m);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        let mut chunked = Vec::new();
+        scan_text(ByteByByte(s.as_bytes()), unit, &[k], false, |m| {
+            chunked.push(m);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(markers, chunked);
+        markers
+    }
+
+    #[test]
+    fn multibyte_chars() {
+        // 'é' and 'ß' take two bytes each, '€' takes three.
+        let markers = text_markers("ééßß€a", Unit::Chars, 3);
+        assert_eq!(markers.len(), 1);
+        assert_eq!(markers[0].position, 6);
+        assert_eq!(markers[0].char_offset, Some(6));
+        assert_eq!(markers[0].byte_offset, 12);
+    }
+
+    #[test]
+    fn grapheme_clusters() {
+        // "e\u{301}" is a single grapheme made of two chars, composed into 'é' by NFC.
+        let s = "e\u{301}e\u{301}ae";
+        assert_eq!(text_markers(s, Unit::Chars, 3)[0].position, 4);
+        // NFC reorders the marks of "q\u{307}\u{323}", so all three chars end with the cluster.
+        let markers = text_markers("q\u{307}\u{323}ab", Unit::Chars, 3);
+        assert_eq!(markers[0].position, 3);
+        assert_eq!(markers[0].char_offset, Some(3));
+        assert_eq!(markers[0].byte_offset, 5);
+        let markers = text_markers(s, Unit::Graphemes, 3);
+        assert_eq!(markers.len(), 1);
+        assert_eq!(markers[0].position, 4);
+        assert_eq!(markers[0].char_offset, Some(6));
+        assert_eq!(markers[0].byte_offset, 8);
+    }
+
+    #[test]
+    fn normalization() {
+        let markers = text_markers("e\u{301}\u{e9}ae\n", Unit::Chars, 2);
+        assert_eq!(
+            markers.iter().map(|m| m.position).collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+    }
+
+    #[test]
+    fn offsets_in_input() {
+        let markers = text_markers("  abcd\n", Unit::Chars, 4);
+        assert_eq!(markers.len(), 1);
+        assert_eq!(markers[0].position, 4);
+        assert_eq!(markers[0].char_offset, Some(6));
+        assert_eq!(markers[0].byte_offset, 6);
+
+        let markers = text_markers("e\u{301}abc", Unit::Chars, 4);
+        assert_eq!(markers[0].position, 4);
+        assert_eq!(markers[0].char_offset, Some(5));
+        assert_eq!(markers[0].byte_offset, 6);
+
+        // Inner whitespace counts, surrounding whitespace does not.
+        let markers = text_markers("\n\ta b\n\n", Unit::Chars, 3);
+        assert_eq!(markers[0].position, 3);
+        assert_eq!(markers[0].byte_offset, 5);
+        assert!(text_markers("ab \n", Unit::Chars, 3).is_empty());
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let result = scan_text(&b"ab\xffcd"[..], Unit::Chars, &[2], false, |_| {
+            ControlFlow::Continue(())
+        });
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        let truncated = scan_text(&"abé".as_bytes()[..3], Unit::Chars, &[4], false, |_| {
+            ControlFlow::Continue(())
+        });
+        assert!(truncated.is_err());
+    }
+
+    #[test]
+    fn stops_on_break() {
+        let mut first = None;
+        let consumed = scan(
+            "mjqjpqmgbljsphdztnvjfqwrcgsmlb".as_bytes(),
+            &[4],
+            false,
+            |m| {
+                first = Some(m.position);
+                ControlFlow::Break(())
+            },
+        )
+        .unwrap();
+        assert_eq!(first, Some(7));
+        assert_eq!(consumed, 7);
+    }
+}
@@ -1,35 +1,86 @@
-use std::fs::read_to_string;
+use std::fs::File;
+use std::io::{self, Read};
+use std::ops::ControlFlow;
 use std::path::PathBuf;
 
 use clap::Parser;
+use color_eyre::eyre::{bail, WrapErr};
+use itertools::Itertools;
+
+use crate::detector::{scan, scan_text, Marker, Unit};
+
+mod detector;
 
 #[derive(Parser, Debug)]
 #[command(author, version)]
 struct Cli {
-    /// Input
+    /// Input ("-" for stdin)
     #[arg(value_name = "FILE")]
     #[arg(default_value = "data/sample.txt")]
     input: PathBuf,
+
+    /// Window sizes to track [default: 4,14]
+    #[arg(long, value_name = "K", value_delimiter = ',')]
+    #[arg(value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    window: Vec<usize>,
+
+    /// Report every marker instead of only the first one for each window size
+    #[arg(long)]
+    all: bool,
+
+    /// Report only markers not overlapping the previous marker of the same size
+    #[arg(long, requires = "all")]
+    non_overlapping: bool,
+
+    /// Unit of text in which markers are looked for
+    #[arg(long, value_enum, default_value_t = Unit::Chars)]
+    unit: Unit,
+
+    /// Stream the input as raw bytes, without trimming or normalizing it
+    #[arg(long, conflicts_with = "unit")]
+    raw: bool,
 }
 
-fn all_different(s: &[u8]) -> bool {
-    for i in 0..s.len() {
-        for j in (i + 1)..s.len() {
-            if s[i] == s[j] {
-                return false;
-            }
-        }
+fn open_input(args: &Cli) -> io::Result<Box<dyn Read>> {
+    if args.input.as_os_str() == "-" {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(File::open(&args.input)?))
     }
-    return true;
 }
 
-fn solve(s: &str, k: usize) -> usize {
-    for i in k..s.len() {
-        if all_different(s[i - k..i].as_bytes()) {
-            return i;
-        }
+/// Streams `input` to the detectors, either as raw bytes or as trimmed and normalized
+/// text. Returns the number of units consumed, along with their name.
+fn scan_input(
+    input: impl Read,
+    args: &Cli,
+    windows: &[usize],
+    non_overlapping: bool,
+    on_marker: impl FnMut(Marker) -> ControlFlow<()>,
+) -> color_eyre::Result<(u64, &'static str)> {
+    if args.raw {
+        let consumed = scan(input, windows, non_overlapping, on_marker)?;
+        return Ok((consumed, "bytes"));
     }
-    panic!("Could not find")
+    let consumed = scan_text(input, args.unit, windows, non_overlapping, on_marker)
+        .wrap_err("Could not read the input as UTF-8 text (use --raw for binary input)")?;
+    let name = match args.unit {
+        Unit::Chars => "chars",
+        Unit::Graphemes => "graphemes",
+    };
+    Ok((consumed, name))
+}
+
+/// Number of chars up to the end of the first marker of size `k` in the text `s`,
+/// once trimmed and normalized.
+fn solve(s: &str, k: usize) -> usize {
+    let mut first = None;
+    scan_text(s.as_bytes(), Unit::Chars, &[k], false, |m| {
+        first = Some(m.position);
+        ControlFlow::Break(())
+    })
+    .expect("Reading from memory cannot fail");
+    first.expect("Could not find") as usize
 }
 
 fn solve_part_one(s: &str) -> usize {
@@ -46,16 +97,82 @@ fn main() -> color_eyre::Result<()> {
     let args = Cli::parse();
     println!("args = {:?}", args);
 
-    let s = read_to_string(args.input)?;
-    println!("Input string length: {}", s.len());
+    // The puzzle itself, on the whole input. The other modes stream it.
+    if args.window.is_empty() && !args.all && !args.raw && matches!(args.unit, Unit::Chars) {
+        let mut s = String::new();
+        open_input(&args)?
+            .read_to_string(&mut s)
+            .wrap_err("Could not read the input as UTF-8 text (use --raw for binary input)")?;
+
+        println!("==> Solving part one...");
+        let ans1 = solve_part_one(&s);
+        println!("Answer: {}", ans1);
 
-    println!("==> Solving part one...");
-    let ans1 = solve_part_one(&s);
-    println!("Answer: {}", ans1);
+        println!("==> Solving part two...");
+        let ans2 = solve_part_two(&s);
+        println!("Answer: {}", ans2);
 
-    println!("==> Solving part two...");
-    let ans2 = solve_part_two(&s);
-    println!("Answer: {}", ans2);
+        return Ok(());
+    }
+
+    let windows = if args.window.is_empty() {
+        vec![4, 14]
+    } else {
+        args.window.iter().copied().unique().collect()
+    };
+
+    if args.all {
+        println!(
+            "==> Searching for all markers of size {}...",
+            windows.iter().join(", ")
+        );
+        let mut counts = vec![0usize; windows.len()];
+        let (consumed, units) = scan_input(
+            open_input(&args)?,
+            &args,
+            &windows,
+            args.non_overlapping,
+            |m| {
+                println!("Marker of size {} at {}", m.window, m);
+                counts[windows.iter().position(|&k| k == m.window).unwrap()] += 1;
+                ControlFlow::Continue(())
+            },
+        )?;
+        println!("Consumed {} {}", consumed, units);
+        for (k, count) in windows.iter().zip(&counts) {
+            println!("Found {} marker(s) of size {}", count, k);
+        }
+        return Ok(());
+    }
+
+    println!(
+        "==> Searching for the first markers of size {}...",
+        windows.iter().join(", ")
+    );
+    let mut first = vec![None; windows.len()];
+    let mut remaining = windows.len();
+    let (consumed, units) = scan_input(open_input(&args)?, &args, &windows, false, |m| {
+        let i = windows.iter().position(|&k| k == m.window).unwrap();
+        if first[i].is_none() {
+            first[i] = Some(m);
+            remaining -= 1;
+        }
+        if remaining == 0 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    })?;
+    println!("Consumed {} {}", consumed, units);
+    for (k, marker) in windows.iter().zip(&first) {
+        match marker {
+            Some(marker) => println!("Marker of size {}: {}", k, marker),
+            None => println!("Marker of size {}: not found", k),
+        }
+    }
+    if remaining > 0 {
+        bail!("Could not find {} marker(s)", remaining);
+    }
 
     Ok(())
 }
@@ -63,6 +180,7 @@ fn main() -> color_eyre::Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::rng::XorShift;
 
     #[test]
     fn part1_sample1() {
@@ -123,4 +241,55 @@ mod tests {
         let s = "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw";
         assert_eq!(26, solve_part_two(s));
     }
+
+    /// Position right after the first window of `k` pairwise distinct bytes, if any.
+    fn solve_bytes(s: &[u8], k: usize) -> Option<u64> {
+        let mut first = None;
+        scan(s, &[k], false, |m| {
+            first = Some(m.position);
+            ControlFlow::Break(())
+        })
+        .unwrap();
+        first
+    }
+
+    fn solve_naive(s: &[u8], k: usize) -> Option<u64> {
+        (k..=s.len())
+            .find(|&i| {
+                let w = &s[i - k..i];
+                (0..k).all(|a| (a + 1..k).all(|b| w[a] != w[b]))
+            })
+            .map(|i| i as u64)
+    }
+
+    #[test]
+    fn large_windows() {
+        for k in 1..=256 {
+            // Leading zeros, then all bytes below `k` exactly once.
+            let mut s = vec![0u8; 3];
+            s.extend((0..k).map(|b| b as u8));
+            let expected = if k == 1 { 1 } else { k as u64 + 3 };
+            assert_eq!(solve_bytes(&s, k), Some(expected), "k = {}", k);
+            assert_eq!(solve_bytes(&s, k + 1), None, "k = {}", k + 1);
+        }
+    }
+
+    #[test]
+    fn matches_naive() {
+        let mut rng = XorShift::new(42);
+        for alphabet in [2, 5, 16, 40, 256] {
+            let s = (0..3000)
+                .map(|_| rng.below(alphabet) as u8)
+                .collect::<Vec<_>>();
+            for k in [1, 2, 3, 4, 7, 14, 20, 33, 64, 256] {
+                assert_eq!(
+                    solve_bytes(&s, k),
+                    solve_naive(&s, k),
+                    "alphabet {}, k {}",
+                    alphabet,
+                    k
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,71 @@
+use std::time::Instant;
+
+use common::rng::XorShift;
+
+use crate::fs::{Entry, FileSystem};
+use crate::parser::parse;
+
+/// Generates a transcript of a tree nested `depth` levels deep: every level has a few
+/// files and a side directory, which is listed on the way back up.
+pub fn generate(depth: usize, seed: u64) -> Vec<String> {
+    let mut rng = XorShift::new(seed);
+    let mut lines = vec!["$ cd /".to_string()];
+    for _ in 0..depth {
+        lines.push("$ ls".to_string());
+        lines.push("dir next".to_string());
+        lines.push("dir side".to_string());
+        for i in 0..1 + rng.below(3) {
+            lines.push(format!("{} file{}.txt", 1 + rng.below(100_000), i));
+        }
+        lines.push("$ cd next".to_string());
+    }
+    lines.push("$ ls".to_string());
+    lines.push(format!("{} leaf.txt", 1 + rng.below(100_000)));
+    for _ in 0..depth {
+        lines.push("$ cd ..".to_string());
+        lines.push("$ cd side".to_string());
+        lines.push("$ ls".to_string());
+        lines.push(format!("{} side.txt", 1 + rng.below(100_000)));
+        lines.push("$ cd ..".to_string());
+    }
+    lines
+}
+
+/// Reference implementation, summing the subtree of every directory separately.
+fn dir_sizes_naive(fs: &FileSystem) -> Vec<usize> {
+    let mut sizes = Vec::new();
+    for dir in fs.iter().filter(|&id| fs.is_dir(id)) {
+        let mut total = 0;
+        let mut stack = vec![dir];
+        while let Some(id) = stack.pop() {
+            match fs.node(id).entry {
+                Entry::Dir { .. } => stack.extend(fs.children(id)),
+                Entry::File { size } => total += size,
+            }
+        }
+        sizes.push(total);
+    }
+    sizes
+}
+
+fn measure<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    println!("{:>16}: {:?}", name, start.elapsed());
+    result
+}
+
+pub fn run(depth: usize) {
+    println!("==> Generating a transcript {} levels deep...", depth);
+    let lines = generate(depth, 42);
+    println!("Transcript lines: {}", lines.len());
+
+    println!("==> Benchmarking...");
+    let fs = measure("parse", || parse(&lines).unwrap().fs);
+    let a = measure("naive", || dir_sizes_naive(&fs));
+    let b = measure("memoized", || fs.dir_sizes());
+    assert_eq!(a, b);
+    let c = measure("memoized again", || fs.dir_sizes());
+    assert_eq!(b, c);
+    println!("Root size: {}", b[0]);
+}
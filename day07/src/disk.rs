@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::fs::{Entry, FileSystem, NodeId};
+
+/// Reads the directory tree under `root` from disk, not descending deeper than `max_depth`
+/// levels. Directories at the depth limit are included, but empty. Symbolic links and
+/// other special files are skipped with a warning, as are names that cannot appear in
+/// a transcript line (containing a line break).
+pub fn scan(root: &Path, max_depth: usize) -> io::Result<(FileSystem, Vec<String>)> {
+    let mut fs = FileSystem::new();
+    let mut warnings = Vec::new();
+    // Directories on the path to the current entry, indexed by depth; `None` if skipped.
+    let mut ancestors: Vec<Option<NodeId>> = vec![Some(FileSystem::ROOT)];
+    for entry in WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(max_depth)
+        .sort_by_file_name()
+    {
+        let entry = entry?;
+        ancestors.truncate(entry.depth());
+        let Some(parent) = ancestors[entry.depth() - 1] else {
+            ancestors.push(None);
+            continue;
+        };
+        let name = entry.file_name().to_string_lossy();
+        let file_type = entry.file_type();
+        let id = if name.contains(['\n', '\r', '/']) {
+            warnings.push(format!("Skipping {:?}: bad name", entry.path()));
+            None
+        } else if file_type.is_dir() {
+            Some(fs.add_dir(parent, &name))
+        } else if file_type.is_file() {
+            let size = entry.metadata()?.len() as usize;
+            Some(fs.add_file(parent, &name, size))
+        } else {
+            warnings.push(format!("Skipping {:?}: not a regular file", entry.path()));
+            None
+        };
+        let id = id
+            .transpose()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        ancestors.push(id);
+    }
+    Ok((fs, warnings))
+}
+
+/// Recreates `fs` under the existing directory `root`, with every file made sparse:
+/// it has the right length but occupies (almost) no disk space.
+pub fn materialize(fs: &FileSystem, root: &Path) -> io::Result<()> {
+    for id in fs.iter().skip(1) {
+        let path = root.join(&fs.path(id)[1..]);
+        match fs.node(id).entry {
+            Entry::Dir { .. } => fs::create_dir(&path)?,
+            Entry::File { size } => File::create(&path)?.set_len(size as u64)?,
+        }
+    }
+    Ok(())
+}
+
+/// Apparent size of every directory under `root`, keyed by its path relative to `root`
+/// (`/` for `root` itself), counting regular files no deeper than `max_depth` levels.
+///
+/// Reads the directories with plain [`fs::read_dir`], independently of [`scan`], so the
+/// two can be checked against each other.
+pub fn apparent_sizes(root: &Path, max_depth: usize) -> io::Result<BTreeMap<String, u64>> {
+    let mut sizes = BTreeMap::new();
+    let mut stack = vec![(PathBuf::from(root), String::new(), 0)];
+    while let Some((dir, path, depth)) = stack.pop() {
+        sizes.entry(dir_key(&path)).or_insert(0);
+        if depth >= max_depth {
+            continue;
+        }
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let child = format!("{}/{}", path, entry.file_name().to_string_lossy());
+            if file_type.is_dir() {
+                stack.push((entry.path(), child, depth + 1));
+            } else if file_type.is_file() {
+                let size = entry.metadata()?.len();
+                // Add the file to every directory on its path, from the root down.
+                for (i, _) in child.match_indices('/') {
+                    *sizes.entry(dir_key(&child[..i])).or_insert(0) += size;
+                }
+            }
+        }
+    }
+    Ok(sizes)
+}
+
+/// Absolute path of a directory given as `/`-prefixed segments, the root being empty.
+fn dir_key(path: &str) -> String {
+    if path.is_empty() {
+        "/".to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Compares the directory sizes of `fs` with `expected` (as computed by [`apparent_sizes`]),
+/// describing every directory that is missing on either side or differs in size.
+pub fn compare_sizes(fs: &FileSystem, expected: &BTreeMap<String, u64>) -> Vec<String> {
+    let mut mismatches = Vec::new();
+    let mut seen = 0;
+    for id in fs.iter().filter(|&id| fs.is_dir(id)) {
+        let path = fs.path(id);
+        match expected.get(&path) {
+            Some(&size) if size == fs.size(id) as u64 => {}
+            Some(&size) => mismatches.push(format!(
+                "{}: reconstructed {}, on disk {}",
+                path,
+                fs.size(id),
+                size
+            )),
+            None => mismatches.push(format!("{}: not found on disk", path)),
+        }
+        seen += usize::from(expected.contains_key(&path));
+    }
+    if seen < expected.len() {
+        for path in expected.keys().filter(|path| fs.lookup(path).is_none()) {
+            mismatches.push(format!("{}: missing from the transcript", path));
+        }
+    }
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use crate::report::render_tree;
+    use crate::writer::{write_transcript, Order};
+
+    #[test]
+    fn materialize_and_scan() {
+        let mut fs = FileSystem::new();
+        let a = fs.add_dir(FileSystem::ROOT, "a").unwrap();
+        let e = fs.add_dir(a, "e").unwrap();
+        fs.add_file(e, "i", 584).unwrap();
+        fs.add_file(a, "f f", 29116).unwrap();
+        fs.add_file(FileSystem::ROOT, "b.txt", 14848514).unwrap();
+        fs.add_dir(FileSystem::ROOT, "empty").unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        materialize(&fs, dir.path()).unwrap();
+        let (scanned, warnings) = scan(dir.path(), usize::MAX).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(
+            render_tree(&scanned, FileSystem::ROOT, usize::MAX),
+            render_tree(&fs, FileSystem::ROOT, usize::MAX)
+        );
+
+        let (shallow, _) = scan(dir.path(), 1).unwrap();
+        assert_eq!(shallow.iter().count(), 4);
+        assert_eq!(shallow.size(FileSystem::ROOT), 14848514);
+    }
+
+    #[test]
+    fn transcript_matches_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("my dir/sub dir")).unwrap();
+        fs::create_dir(root.join("empty")).unwrap();
+        fs::write(root.join("my dir/sub dir/f"), [0; 100]).unwrap();
+        fs::write(root.join("my dir/a file"), [0; 20]).unwrap();
+        fs::write(root.join("b"), [0; 3]).unwrap();
+        fs::write(root.join("bad\rname"), [0; 5]).unwrap();
+
+        let expected = apparent_sizes(root, usize::MAX).unwrap();
+        assert_eq!(expected["/"], 128);
+        assert_eq!(expected["/my dir"], 120);
+        assert_eq!(expected["/empty"], 0);
+
+        let (scanned, warnings) = scan(root, usize::MAX).unwrap();
+        assert_eq!(warnings.len(), 1);
+        let t = parse(&write_transcript(&scanned, Order::Name)).unwrap();
+        assert!(t.warnings.is_empty(), "{:?}", t.warnings);
+        let mismatches = compare_sizes(&t.fs, &expected);
+        assert_eq!(mismatches, ["/: reconstructed 123, on disk 128"]);
+
+        fs::remove_file(root.join("bad\rname")).unwrap();
+        let expected = apparent_sizes(root, usize::MAX).unwrap();
+        assert!(compare_sizes(&t.fs, &expected).is_empty());
+        let shallow = apparent_sizes(root, 1).unwrap();
+        assert_eq!(shallow["/"], 3);
+        assert_eq!(shallow["/my dir"], 0);
+        assert!(!shallow.contains_key("/my dir/sub dir"));
+    }
+}
@@ -0,0 +1,331 @@
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use itertools::Itertools;
+
+/// Index of a node in the [`FileSystem`] arena.
+pub type NodeId = usize;
+
+#[derive(Debug, Clone)]
+pub enum Entry {
+    Dir { children: BTreeMap<String, NodeId> }, // {name: node}
+    File { size: usize },
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub name: String,
+    pub parent: Option<NodeId>,
+    pub entry: Entry,
+}
+
+/// Filesystem tree stored as an arena of nodes, with the root directory at [`FileSystem::ROOT`].
+///
+/// Nodes refer to their parent and children by index, so the tree can be traversed in
+/// any direction and mutated without shared ownership.
+///
+/// All traversals use explicit stacks, so arbitrarily deep trees are fine.
+#[derive(Debug, Clone)]
+pub struct FileSystem {
+    nodes: Vec<Node>,
+    /// Total size of every node, computed on demand and reset by any change to the tree.
+    sizes: OnceCell<Vec<usize>>,
+}
+
+impl FileSystem {
+    pub const ROOT: NodeId = 0;
+
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                name: "/".to_string(),
+                parent: None,
+                entry: Entry::Dir {
+                    children: BTreeMap::new(),
+                },
+            }],
+            sizes: OnceCell::new(),
+        }
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id].parent
+    }
+
+    pub fn is_dir(&self, id: NodeId) -> bool {
+        matches!(self.nodes[id].entry, Entry::Dir { .. })
+    }
+
+    /// Child named `name` of the directory `id`.
+    pub fn child(&self, id: NodeId, name: &str) -> Option<NodeId> {
+        match &self.nodes[id].entry {
+            Entry::Dir { children } => children.get(name).copied(),
+            Entry::File { .. } => None,
+        }
+    }
+
+    /// Children of the directory `id`, ordered by name. Files have no children.
+    pub fn children(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let children = match &self.nodes[id].entry {
+            Entry::Dir { children } => Some(children.values().copied()),
+            Entry::File { .. } => None,
+        };
+        children.into_iter().flatten()
+    }
+
+    fn add_node(&mut self, parent: NodeId, name: &str, entry: Entry) -> Result<NodeId, String> {
+        let id = self.nodes.len();
+        match &mut self.nodes[parent].entry {
+            Entry::Dir { children } => {
+                if children.contains_key(name) {
+                    return Err(format!(
+                        "{:?} already exists in {}",
+                        name,
+                        self.path(parent)
+                    ));
+                }
+                children.insert(name.to_string(), id);
+            }
+            Entry::File { .. } => {
+                return Err(format!("{} is not a directory", self.path(parent)));
+            }
+        }
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: Some(parent),
+            entry,
+        });
+        self.sizes.take();
+        Ok(id)
+    }
+
+    /// Adds an empty directory `name` to the directory `parent`.
+    pub fn add_dir(&mut self, parent: NodeId, name: &str) -> Result<NodeId, String> {
+        let entry = Entry::Dir {
+            children: BTreeMap::new(),
+        };
+        self.add_node(parent, name, entry)
+    }
+
+    /// Adds a file `name` of the given size to the directory `parent`.
+    pub fn add_file(&mut self, parent: NodeId, name: &str, size: usize) -> Result<NodeId, String> {
+        self.add_node(parent, name, Entry::File { size })
+    }
+
+    /// Changes the size of the file `id`.
+    pub fn set_file_size(&mut self, id: NodeId, new_size: usize) {
+        match &mut self.nodes[id].entry {
+            Entry::File { size } => *size = new_size,
+            Entry::Dir { .. } => panic!("{} is not a file", self.path(id)),
+        }
+        self.sizes.take();
+    }
+
+    /// Removes the node `id` with everything under it. The removed nodes stay in the arena,
+    /// unreachable from the root, so all other ids remain valid.
+    pub fn remove(&mut self, id: NodeId) -> Result<(), String> {
+        let parent = self.nodes[id].parent.ok_or("Cannot remove the root")?;
+        if self.child(parent, &self.nodes[id].name) != Some(id) {
+            return Err(format!("{} is already removed", self.path(id)));
+        }
+        let name = self.nodes[id].name.clone();
+        if let Entry::Dir { children } = &mut self.nodes[parent].entry {
+            children.remove(&name);
+        }
+        self.sizes.take();
+        Ok(())
+    }
+
+    /// Absolute path of the node, e.g. `/a/e`.
+    pub fn path(&self, id: NodeId) -> String {
+        let mut names = Vec::new();
+        let mut cur = id;
+        while let Some(parent) = self.nodes[cur].parent {
+            names.push(self.nodes[cur].name.as_str());
+            cur = parent;
+        }
+        format!("/{}", names.iter().rev().join("/"))
+    }
+
+    /// Node at the absolute path, e.g. `/a/e`.
+    pub fn lookup(&self, path: &str) -> Option<NodeId> {
+        path.split('/')
+            .filter(|s| !s.is_empty())
+            .try_fold(Self::ROOT, |id, name| self.child(id, name))
+    }
+
+    /// All nodes in path order (pre-order, children sorted by name), starting from the root.
+    pub fn iter(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.walk(Self::ROOT, usize::MAX).map(|(id, _)| id)
+    }
+
+    /// Nodes under `start` (inclusive) in path order, with their depth relative to `start`,
+    /// not descending deeper than `max_depth`.
+    pub fn walk(
+        &self,
+        start: NodeId,
+        max_depth: usize,
+    ) -> impl Iterator<Item = (NodeId, usize)> + '_ {
+        let mut stack = vec![(start, 0)];
+        std::iter::from_fn(move || {
+            let (id, depth) = stack.pop()?;
+            if depth < max_depth {
+                let len = stack.len();
+                stack.extend(self.children(id).map(|child| (child, depth + 1)));
+                stack[len..].reverse();
+            }
+            Some((id, depth))
+        })
+    }
+
+    /// Total size of all files under the node.
+    pub fn size(&self, id: NodeId) -> usize {
+        self.sizes.get_or_init(|| self.compute_sizes())[id]
+    }
+
+    /// Sizes of all nodes in a single post-order pass: the reversed path order
+    /// visits every node after all of its descendants.
+    fn compute_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.nodes.len()];
+        let order = self.iter().collect_vec();
+        for &id in order.iter().rev() {
+            let node = &self.nodes[id];
+            if let Entry::File { size } = node.entry {
+                sizes[id] = size;
+            }
+            if let Some(parent) = node.parent {
+                sizes[parent] += sizes[id];
+            }
+        }
+        sizes
+    }
+
+    /// Sizes of all directories in path order, starting with the root.
+    pub fn dir_sizes(&self) -> Vec<usize> {
+        self.iter()
+            .filter(|&id| self.is_dir(id))
+            .map(|id| self.size(id))
+            .collect()
+    }
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for FileSystem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        enum Step {
+            Open(NodeId),
+            Separator,
+            Close,
+        }
+
+        let mut stack = vec![Step::Open(Self::ROOT)];
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Open(id) => {
+                    let node = &self.nodes[id];
+                    match &node.entry {
+                        Entry::Dir { children } => {
+                            write!(f, "Dir({:?}: [", node.name)?;
+                            stack.push(Step::Close);
+                            for (i, &child) in children.values().rev().enumerate() {
+                                if i > 0 {
+                                    stack.push(Step::Separator);
+                                }
+                                stack.push(Step::Open(child));
+                            }
+                        }
+                        Entry::File { size } => write!(f, "File({:?}: {})", node.name, size)?,
+                    }
+                }
+                Step::Separator => write!(f, ", ")?,
+                Step::Close => write!(f, "])")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> FileSystem {
+        let mut fs = FileSystem::new();
+        let a = fs.add_dir(FileSystem::ROOT, "a").unwrap();
+        let e = fs.add_dir(a, "e").unwrap();
+        fs.add_file(e, "i", 584).unwrap();
+        fs.add_file(a, "f", 29116).unwrap();
+        fs.add_file(FileSystem::ROOT, "b.txt", 14848514).unwrap();
+        fs.add_dir(FileSystem::ROOT, "d").unwrap();
+        fs
+    }
+
+    #[test]
+    fn paths_and_lookup() {
+        let fs = sample();
+        for id in fs.iter() {
+            assert_eq!(fs.lookup(&fs.path(id)), Some(id));
+        }
+        assert_eq!(fs.lookup("/"), Some(FileSystem::ROOT));
+        assert_eq!(fs.lookup("/a/x"), None);
+        assert_eq!(fs.lookup("/b.txt/x"), None);
+    }
+
+    #[test]
+    fn path_order() {
+        let mut fs = sample();
+        let paths = fs.iter().map(|id| fs.path(id)).collect_vec();
+        assert_eq!(paths, ["/", "/a", "/a/e", "/a/e/i", "/a/f", "/b.txt", "/d"]);
+        assert_eq!(
+            fs.dir_sizes(),
+            [14848514 + 29116 + 584, 29116 + 584, 584, 0]
+        );
+        assert!(fs.add_file(FileSystem::ROOT, "a", 1).is_err());
+    }
+
+    #[test]
+    fn sizes_are_invalidated() {
+        let mut fs = sample();
+        let e = fs.lookup("/a/e").unwrap();
+        assert_eq!(fs.size(e), 584);
+        fs.add_file(e, "j", 16).unwrap();
+        assert_eq!(fs.size(e), 600);
+        assert_eq!(fs.size(FileSystem::ROOT), 14848514 + 29116 + 600);
+    }
+
+    #[test]
+    fn remove() {
+        let mut fs = sample();
+        let a = fs.lookup("/a").unwrap();
+        let e = fs.lookup("/a/e").unwrap();
+        fs.remove(e).unwrap();
+        assert_eq!(fs.size(a), 29116);
+        assert_eq!(fs.lookup("/a/e"), None);
+        assert!(fs.remove(e).is_err());
+        assert!(fs.remove(FileSystem::ROOT).is_err());
+        assert_eq!(fs.dir_sizes(), [14848514 + 29116, 29116, 0]);
+    }
+
+    #[test]
+    fn deep_tree() {
+        let mut fs = FileSystem::new();
+        let mut cur = FileSystem::ROOT;
+        for _ in 0..100_000 {
+            cur = fs.add_dir(cur, "d").unwrap();
+            fs.add_file(cur, "f", 1).unwrap();
+        }
+        assert_eq!(fs.size(FileSystem::ROOT), 100_000);
+        assert_eq!(fs.dir_sizes().len(), 100_001);
+        assert_eq!(fs.to_string().matches("Dir(").count(), 100_001);
+    }
+}
@@ -1,14 +1,28 @@
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::fmt::{Display, Formatter};
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::PathBuf;
-use std::rc::Rc;
 
 use clap::Parser;
+use color_eyre::eyre::{bail, eyre};
 use itertools::Itertools;
 
+use crate::fs::{FileSystem, NodeId};
+use crate::parser::{parse, Transcript};
+use crate::planner::plan;
+use crate::query::Query;
+use crate::report::{du, render_tree};
+use crate::writer::{write_transcript, Order};
+
+mod bench;
+mod disk;
+mod fs;
+mod parser;
+mod planner;
+mod query;
+mod report;
+mod writer;
+
 #[derive(Parser, Debug)]
 #[command(author, version)]
 struct Cli {
@@ -16,95 +30,102 @@ struct Cli {
     #[arg(value_name = "FILE")]
     #[arg(default_value = "data/sample.txt")]
     input: PathBuf,
-}
 
-enum Entry {
-    Dir(Rc<RefCell<Directory>>),
-    File { name: String, size: usize },
-}
+    /// Print the total size of the given absolute path, e.g. "/a/e"
+    #[arg(long, value_name = "PATH")]
+    size_of: Option<String>,
 
-impl Entry {
-    fn name(&self) -> String {
-        match self {
-            Entry::Dir(dir) => dir.borrow().name.clone(),
-            Entry::File { name, .. } => name.clone(),
-        }
-    }
+    /// Print the tree under PATH with sizes
+    #[arg(long, value_name = "PATH", num_args = 0..=1, default_missing_value = "/")]
+    tree: Option<String>,
 
-    fn size(&self) -> usize {
-        match self {
-            Entry::Dir(dir) => dir.borrow().size(),
-            Entry::File { size, .. } => *size,
-        }
-    }
-}
+    /// Maximum depth shown by --tree
+    #[arg(long, default_value_t = usize::MAX, hide_default_value = true)]
+    depth: usize,
 
-impl Display for Entry {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Entry::Dir(dir) => write!(
-                f,
-                "Dir({:?}: [{}])",
-                dir.borrow().name,
-                dir.borrow()
-                    .children
-                    .values()
-                    .map(|e| e.to_string())
-                    .join(", ")
-            ),
-            Entry::File { name, size } => write!(f, "File({:?}: {})", name, size),
-        }
-    }
-}
+    /// List the directories under PATH by size, largest first
+    #[arg(long, value_name = "PATH", num_args = 0..=1, default_missing_value = "/")]
+    du: Option<String>,
 
-struct Directory {
-    name: String,
-    parent: Option<Rc<RefCell<Directory>>>,
-    children: HashMap<String, Entry>, // {name: entry}
-}
+    /// List the directories matching a query, e.g. 'size <= 100000 and name ~ "a*"'
+    #[arg(long, value_name = "QUERY")]
+    query: Option<Query>,
 
-impl Directory {
-    fn new(name: impl Into<String>, parent: Option<Rc<RefCell<Directory>>>) -> Self {
-        Self {
-            name: name.into(),
-            parent,
-            children: HashMap::new(),
-        }
-    }
+    /// Maximum size of the directories counted in part one
+    #[arg(long, default_value_t = 100000)]
+    threshold: usize,
 
-    fn add_child(&mut self, child: Entry) {
-        self.children.insert(child.name(), child);
-    }
+    /// Total disk space
+    #[arg(long, default_value_t = 70000000)]
+    disk_size: usize,
 
-    fn size(&self) -> usize {
-        self.children.values().map(|e| e.size()).sum()
-    }
+    /// Free space needed for the update
+    #[arg(long, default_value_t = 30000000)]
+    needed: usize,
 
-    fn dir_sizes(&self) -> Vec<usize> {
-        let mut sizes = Vec::new();
-        sizes.push(self.size());
-        for child in self.children.values() {
-            match child {
-                Entry::Dir(dir) => {
-                    sizes.extend(dir.borrow().dir_sizes());
-                }
-                Entry::File { .. } => {}
-            }
+    /// Generate a transcript from the real directory DIR instead of reading one, then
+    /// replay it and check the directory sizes against DIR
+    #[arg(long, value_name = "DIR")]
+    generate: Option<PathBuf>,
+
+    /// Order of the entries in the generated transcript
+    #[arg(long, value_enum, default_value_t = Order::Name, requires = "generate")]
+    order: Order,
+
+    /// Maximum depth of the directories read by --generate
+    #[arg(long, default_value_t = usize::MAX, hide_default_value = true, requires = "generate")]
+    max_depth: usize,
+
+    /// Write the generated transcript to FILE instead of stdout
+    #[arg(long, value_name = "FILE", requires = "generate")]
+    output: Option<PathBuf>,
+
+    /// Recreate the filesystem as sparse files in a temporary directory and compare
+    /// the directory sizes reported by the OS with the reconstructed ones
+    #[arg(long)]
+    materialize: bool,
+
+    /// Also plan the removal of several non-nested directories freeing the needed space
+    /// with as few bytes removed as possible
+    #[arg(long)]
+    plan: bool,
+
+    /// Benchmark directory sizes on a generated transcript nested DEPTH levels deep
+    #[arg(long, value_name = "DEPTH")]
+    bench: Option<usize>,
+}
+
+fn lookup(fs: &FileSystem, path: &str) -> color_eyre::Result<NodeId> {
+    fs.lookup(path)
+        .ok_or_else(|| eyre!("No such file or directory: {}", path))
+}
+
+/// Prints the free space left after removing `removed`, and the resulting tree if asked for.
+fn print_after_removal(fs: &FileSystem, args: &Cli, removed: &[String]) {
+    println!(
+        "Free space after removal: {} of {}",
+        args.disk_size.saturating_sub(fs.size(FileSystem::ROOT)),
+        args.disk_size
+    );
+    if args.tree.is_some() {
+        println!("==> Tree after removing {}:", removed.join(", "));
+        for line in render_tree(fs, FileSystem::ROOT, args.depth) {
+            println!("{}", line);
         }
-        sizes
     }
 }
 
-impl Display for Directory {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "Directory(name = {:?}, parent = {:?}, children = [{}])",
-            self.name,
-            self.parent.as_ref().map(|p| p.borrow().name.clone()),
-            self.children.values().map(|e| e.to_string()).join(", ")
-        )
+/// Checks the directory sizes of `fs` against the ones measured on disk.
+fn check_sizes(fs: &FileSystem, on_disk: &BTreeMap<String, u64>) -> color_eyre::Result<()> {
+    let mismatches = disk::compare_sizes(fs, on_disk);
+    for mismatch in &mismatches {
+        println!("Mismatch at {}", mismatch);
+    }
+    if !mismatches.is_empty() {
+        bail!("Found {} mismatching directories", mismatches.len());
     }
+    println!("All {} directory sizes match the disk", on_disk.len());
+    Ok(())
 }
 
 fn main() -> color_eyre::Result<()> {
@@ -113,63 +134,156 @@ fn main() -> color_eyre::Result<()> {
     let args = Cli::parse();
     println!("args = {:?}", args);
 
-    let lines = BufReader::new(File::open(args.input)?)
-        .lines()
-        .filter_map(|x| x.ok())
-        .collect_vec();
+    if let Some(depth) = args.bench {
+        bench::run(depth);
+        return Ok(());
+    }
 
-    let mut root = Rc::new(RefCell::new(Directory::new("/", None)));
-    let mut cwd = Rc::clone(&root);
-    for line in lines {
-        match line.as_str() {
-            "$ cd /" => {
-                cwd = Rc::clone(&root);
-            }
-            "$ cd .." => {
-                let parent = Rc::clone(cwd.borrow().parent.as_ref().unwrap());
-                cwd = parent;
-            }
-            "$ ls" => {}
-            _ if line.starts_with("$ cd ") => {
-                let name = line[5..].to_string();
-                let child = match &cwd.borrow().children[&name] {
-                    Entry::Dir(dir) => Rc::clone(dir),
-                    Entry::File { .. } => panic!("Cannot 'cd' to a file"),
-                };
-                cwd = child;
+    if let Some(dir) = &args.generate {
+        let (fs, warnings) = disk::scan(dir, args.max_depth)?;
+        for warning in &warnings {
+            println!("Warning: {}", warning);
+        }
+        let transcript = write_transcript(&fs, args.order);
+        match &args.output {
+            Some(path) => {
+                let mut file = BufWriter::new(File::create(path)?);
+                for line in &transcript {
+                    writeln!(file, "{}", line)?;
+                }
+                println!(
+                    "Wrote {} lines to {}, total size {}",
+                    transcript.len(),
+                    path.display(),
+                    fs.size(FileSystem::ROOT)
+                );
             }
-            _ if line.starts_with("dir ") => {
-                let name = line[4..].to_string();
-                let dir = Rc::new(RefCell::new(Directory::new(name, Some(Rc::clone(&cwd)))));
-                let entry = Entry::Dir(dir);
-                cwd.borrow_mut().add_child(entry);
+            None => {
+                for line in &transcript {
+                    println!("{}", line);
+                }
             }
-            _ => {
-                let parts = line.split_whitespace().collect_vec();
-                assert_eq!(parts.len(), 2, "parts: {:?}", parts);
-                let size = parts[0].parse::<usize>().unwrap();
-                let name = parts[1].to_string();
-                let entry = Entry::File { name, size };
-                cwd.borrow_mut().add_child(entry);
+        }
+        let replayed = parse(&transcript).map_err(|e| eyre!(e))?.fs;
+        check_sizes(&replayed, &disk::apparent_sizes(dir, args.max_depth)?)?;
+        return Ok(());
+    }
+
+    let lines = BufReader::new(File::open(&args.input)?)
+        .lines()
+        .map_while(Result::ok)
+        .collect_vec();
+
+    let Transcript { fs, warnings } = parse(&lines).map_err(|e| eyre!(e))?;
+    for warning in &warnings {
+        println!("Warning: {}", warning);
+    }
+    let dirs = fs.iter().filter(|&id| fs.is_dir(id)).count();
+    println!(
+        "Reconstructed {} directories and {} files",
+        dirs,
+        fs.iter().count() - dirs
+    );
+
+    if let Some(path) = &args.size_of {
+        let id = lookup(&fs, path)?;
+        println!("Size of {}: {}", fs.path(id), fs.size(id));
+    }
+
+    if let Some(path) = &args.tree {
+        println!("==> Tree of {}:", path);
+        for line in render_tree(&fs, lookup(&fs, path)?, args.depth) {
+            println!("{}", line);
+        }
+    }
+
+    if let Some(path) = &args.du {
+        println!("==> Directories under {} by size:", path);
+        for id in du(&fs, lookup(&fs, path)?) {
+            println!("{:>12}  {}", fs.size(id), fs.path(id));
+        }
+    }
+
+    if let Some(query) = &args.query {
+        println!("==> Directories matching the query:");
+        let mut count = 0;
+        for (id, depth) in fs.walk(FileSystem::ROOT, usize::MAX) {
+            if fs.is_dir(id) && query.matches(&fs, id, depth) {
+                println!("{:>12}  {}", fs.size(id), fs.path(id));
+                count += 1;
             }
         }
+        println!("Found {} matching directories", count);
+    }
+
+    if args.materialize {
+        let dir = tempfile::tempdir()?;
+        println!("==> Materializing into {}...", dir.path().display());
+        disk::materialize(&fs, dir.path())?;
+        check_sizes(&fs, &disk::apparent_sizes(dir.path(), usize::MAX)?)?;
     }
-    println!("root = {}", root.borrow());
 
-    let dir_sizes = root.borrow().dir_sizes();
+    let dir_sizes = fs.dir_sizes();
 
     println!("==> Solving part one...");
-    let ans1: usize = dir_sizes.iter().filter(|&&s| s <= 100000).sum();
-    println!("Total size under 100000: {}", ans1);
+    let ans1: usize = dir_sizes.iter().filter(|&&s| s <= args.threshold).sum();
+    println!("Total size under {}: {}", args.threshold, ans1);
 
     println!("==> Solving part two...");
-    let need_to_free = dir_sizes[0] - 40000000;
-    let ans2 = *dir_sizes
+    let free = args.disk_size.saturating_sub(dir_sizes[0]);
+    println!("Free space: {} of {}", free, args.disk_size);
+    if free >= args.needed {
+        println!("Already enough free space, nothing to remove");
+        return Ok(());
+    }
+    let need_to_free = args.needed - free;
+    println!("Need to free: {}", need_to_free);
+    let removed = fs
         .iter()
-        .filter(|&&s| s >= need_to_free)
-        .min()
-        .unwrap();
-    println!("Total size of removed dir: {}", ans2);
+        .filter(|&id| fs.is_dir(id) && fs.size(id) >= need_to_free)
+        .min_by_key(|&id| fs.size(id));
+    match removed {
+        // The root is a valid answer, but it cannot be removed from the tree.
+        Some(FileSystem::ROOT) => println!(
+            "Total size of removed dir: {} (/, not simulated)",
+            fs.size(FileSystem::ROOT)
+        ),
+        Some(removed) => {
+            println!(
+                "Total size of removed dir: {} ({})",
+                fs.size(removed),
+                fs.path(removed)
+            );
+            let mut after = fs.clone();
+            after.remove(removed).map_err(|e| eyre!(e))?;
+            print_after_removal(&after, &args, &[fs.path(removed)]);
+        }
+        // Several directories together may still be enough.
+        None if args.plan => println!("No single directory frees {} bytes", need_to_free),
+        None => bail!("No directory frees {} bytes", need_to_free),
+    }
+
+    if args.plan {
+        println!("==> Planning a cleanup of several directories...");
+        let Some(plan) = plan(&fs, need_to_free) else {
+            bail!("No set of directories frees {} bytes", need_to_free);
+        };
+        for &id in &plan {
+            println!("{:>12}  {}", fs.size(id), fs.path(id));
+        }
+        let total: usize = plan.iter().map(|&id| fs.size(id)).sum();
+        println!(
+            "Total size of removed dirs: {} ({} more than needed)",
+            total,
+            total - need_to_free
+        );
+        let mut after = fs.clone();
+        for &id in &plan {
+            after.remove(id).map_err(|e| eyre!(e))?;
+        }
+        let paths = plan.iter().map(|&id| fs.path(id)).collect_vec();
+        print_after_removal(&after, &args, &paths);
+    }
 
     Ok(())
 }
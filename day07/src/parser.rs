@@ -0,0 +1,220 @@
+use crate::fs::{Entry, FileSystem, NodeId};
+
+/// Filesystem reconstructed from a transcript, with any warnings produced along the way.
+#[derive(Debug)]
+pub struct Transcript {
+    pub fs: FileSystem,
+    pub warnings: Vec<String>,
+}
+
+/// What the output lines following a command belong to.
+enum Output {
+    /// Listing of the given directory.
+    Listing(NodeId),
+    /// Output of a command we do not know, skipped.
+    Ignored,
+    /// No command yet, or a command that prints nothing.
+    Unexpected,
+}
+
+struct Interpreter {
+    fs: FileSystem,
+    cwd: NodeId,
+    output: Output,
+    warnings: Vec<String>,
+}
+
+/// Replays a terminal transcript of `cd` and `ls` commands, reconstructing the filesystem.
+///
+/// `cd` accepts absolute and relative paths with several segments, and enters directories
+/// that have not been listed yet. Listing a directory again only adds what is new. Unknown
+/// commands and their output are skipped with a warning.
+pub fn parse(lines: &[String]) -> Result<Transcript, String> {
+    let mut interpreter = Interpreter {
+        fs: FileSystem::new(),
+        cwd: FileSystem::ROOT,
+        output: Output::Unexpected,
+        warnings: Vec::new(),
+    };
+    for (i, line) in lines.iter().enumerate() {
+        let warnings = interpreter.warnings.len();
+        interpreter
+            .line(line)
+            .map_err(|e| format!("Line {}: {}", i + 1, e))?;
+        for warning in &mut interpreter.warnings[warnings..] {
+            *warning = format!("Line {}: {}", i + 1, warning);
+        }
+    }
+    Ok(Transcript {
+        fs: interpreter.fs,
+        warnings: interpreter.warnings,
+    })
+}
+
+impl Interpreter {
+    fn line(&mut self, line: &str) -> Result<(), String> {
+        if let Some(command) = line.strip_prefix("$ ") {
+            return self.command(command);
+        }
+        let dir = match self.output {
+            Output::Listing(dir) => dir,
+            Output::Ignored => return Ok(()),
+            Output::Unexpected => {
+                self.warnings.push(format!(
+                    "Output {:?} outside of 'ls', treating it as a listing of {}",
+                    line,
+                    self.fs.path(self.cwd)
+                ));
+                self.cwd
+            }
+        };
+        self.listing(dir, line)
+    }
+
+    /// Runs a command. The argument of `cd` is the rest of the line, so paths may contain spaces.
+    fn command(&mut self, command: &str) -> Result<(), String> {
+        self.output = Output::Unexpected;
+        match command.split_once(' ') {
+            Some(("cd", path)) if !path.is_empty() => self.cwd = self.resolve(path)?,
+            Some(("cd", _)) | None if command.trim() == "cd" => self
+                .warnings
+                .push(format!("Expected a path after 'cd': {:?}", command)),
+            None if command == "ls" => self.output = Output::Listing(self.cwd),
+            _ => {
+                self.warnings.push(format!(
+                    "Unknown command {:?}, skipping its output",
+                    command
+                ));
+                self.output = Output::Ignored;
+            }
+        }
+        Ok(())
+    }
+
+    /// Directory at `path` relative to the current one, created if it was not listed yet.
+    fn resolve(&mut self, path: &str) -> Result<NodeId, String> {
+        let mut cur = if path.starts_with('/') {
+            FileSystem::ROOT
+        } else {
+            self.cwd
+        };
+        for name in path.split('/').filter(|s| !s.is_empty()) {
+            cur = match name {
+                "." => cur,
+                ".." => self.fs.parent(cur).unwrap_or(FileSystem::ROOT),
+                _ => match self.fs.child(cur, name) {
+                    Some(child) if self.fs.is_dir(child) => child,
+                    Some(child) => {
+                        return Err(format!("Cannot 'cd' to a file {}", self.fs.path(child)))
+                    }
+                    None => self.fs.add_dir(cur, name)?,
+                },
+            };
+        }
+        Ok(cur)
+    }
+
+    /// Adds an `ls` output line (`dir <name>` or `<size> <name>`) to the directory `dir`.
+    fn listing(&mut self, dir: NodeId, line: &str) -> Result<(), String> {
+        let (first, name) = line
+            .split_once(' ')
+            .ok_or_else(|| format!("Bad line: {:?}", line))?;
+        if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+            return Err(format!("Bad file name {:?}", name));
+        }
+        let existing = self.fs.child(dir, name);
+        if first == "dir" {
+            match existing {
+                None => {
+                    self.fs.add_dir(dir, name)?;
+                }
+                Some(id) if self.fs.is_dir(id) => {}
+                Some(id) => self.warnings.push(format!(
+                    "{} was listed as a file before, ignoring it as a directory",
+                    self.fs.path(id)
+                )),
+            }
+            return Ok(());
+        }
+
+        let size = first
+            .parse::<usize>()
+            .map_err(|e| format!("Bad file size {:?}: {}", first, e))?;
+        match existing.map(|id| (id, &self.fs.node(id).entry)) {
+            None => {
+                self.fs.add_file(dir, name, size)?;
+            }
+            Some((_, &Entry::File { size: old })) if old == size => {}
+            Some((id, &Entry::File { size: old })) => {
+                self.warnings.push(format!(
+                    "{} was listed with size {} before, now {}",
+                    self.fs.path(id),
+                    old,
+                    size
+                ));
+                self.fs.set_file_size(id, size);
+            }
+            Some((id, Entry::Dir { .. })) => self.warnings.push(format!(
+                "{} was listed as a directory before, ignoring it as a file",
+                self.fs.path(id)
+            )),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(s: &str) -> Vec<String> {
+        s.lines().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn paths() {
+        let t = parse(&lines(
+            "$ cd /a/b\n$ ls\n10 x\n$ cd ../c/./d\n$ ls\n20 y\n$ cd /\n$ cd ..\n$ ls\ndir a\n5 z",
+        ))
+        .unwrap();
+        assert!(t.warnings.is_empty());
+        let fs = &t.fs;
+        assert_eq!(fs.size(fs.lookup("/a/b").unwrap()), 10);
+        assert_eq!(fs.size(fs.lookup("/a/c/d").unwrap()), 20);
+        assert_eq!(fs.size(FileSystem::ROOT), 35);
+    }
+
+    #[test]
+    fn repeated_listing() {
+        let t = parse(&lines(
+            "$ ls\ndir a\n10 x\n$ ls\ndir a\n10 x\n$ cd a\n$ ls\n1 y\n$ cd ..\n$ ls\n12 x\ndir a",
+        ))
+        .unwrap();
+        assert_eq!(t.fs.size(FileSystem::ROOT), 13);
+        assert_eq!(t.warnings.len(), 1);
+        assert!(t.warnings[0].starts_with("Line 12:"), "{:?}", t.warnings);
+    }
+
+    #[test]
+    fn unknown_commands() {
+        let t = parse(&lines("$ pwd\n/\n$ ls\n10 x\n$ cd \n7 y")).unwrap();
+        assert_eq!(t.fs.size(FileSystem::ROOT), 17);
+        assert_eq!(t.warnings.len(), 3);
+        assert!(parse(&lines("$ ls\n10 x\n$ cd x")).is_err());
+        assert!(parse(&lines("$ ls\nten x")).is_err());
+        assert!(parse(&lines("$ ls\ndir a/b")).is_err());
+    }
+
+    #[test]
+    fn names_with_spaces() {
+        let t = parse(&lines(
+            "$ ls\ndir my dir\n$ cd my dir\n$ ls\n3 a file\n$ cd /my dir/sub dir\n$ ls\n4 f",
+        ))
+        .unwrap();
+        assert!(t.warnings.is_empty(), "{:?}", t.warnings);
+        let fs = &t.fs;
+        assert_eq!(fs.size(fs.lookup("/my dir").unwrap()), 7);
+        assert_eq!(fs.size(fs.lookup("/my dir/sub dir").unwrap()), 4);
+        assert_eq!(fs.children(FileSystem::ROOT).count(), 1);
+    }
+}
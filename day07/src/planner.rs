@@ -0,0 +1,174 @@
+use std::collections::HashSet;
+
+use itertools::Itertools;
+
+use crate::fs::{FileSystem, NodeId};
+
+/// Chooses directories to remove, none inside another, freeing at least `need` bytes in
+/// total while removing as little as possible. The root is never removed.
+///
+/// This is a knapsack over the directories in path order, where the subtree of a
+/// directory occupies a contiguous range: at each directory we either remove it and jump
+/// past its whole subtree, or skip it and go on to its first subdirectory. The search
+/// goes depth-first, trying removals first, and abandons a branch once it frees enough
+/// or can no longer free enough even by removing everything after it. It stops early on
+/// a plan freeing exactly `need`.
+///
+/// A branch is fully explored before the search moves on, and the best plan only gets
+/// better, so a position reached again with the same sum cannot lead anywhere new and is
+/// skipped. This bounds the work by the number of distinct (position, sum) pairs below
+/// `need`, like a knapsack table, but only the pairs actually reached are stored.
+/// Returns `None` if even removing everything is not enough.
+pub fn plan(fs: &FileSystem, need: usize) -> Option<Vec<NodeId>> {
+    /// Steps of the search, with a position in path order and the sum removed before it.
+    enum Step {
+        Visit(usize, usize),
+        Take(usize, usize),
+        Untake,
+    }
+
+    let dirs = fs
+        .walk(FileSystem::ROOT, usize::MAX)
+        .filter(|&(id, _)| id != FileSystem::ROOT && fs.is_dir(id))
+        .collect_vec();
+    let n = dirs.len();
+    // Position right after the subtree of each directory.
+    let mut ends = vec![n; n];
+    let mut open: Vec<usize> = Vec::new();
+    for (i, &(_, depth)) in dirs.iter().enumerate() {
+        while let Some(&j) = open.last() {
+            if dirs[j].1 < depth {
+                break;
+            }
+            ends[j] = i;
+            open.pop();
+        }
+        open.push(i);
+    }
+    let sizes = dirs.iter().map(|&(id, _)| fs.size(id)).collect_vec();
+    // Most that can still be removed from each position on.
+    let mut rest = vec![0; n + 1];
+    for i in (0..n).rev() {
+        rest[i] = rest[i + 1].max(sizes[i] + rest[ends[i]]);
+    }
+
+    let mut best: Option<(usize, Vec<usize>)> = None;
+    // Positions of the directories removed on the current branch.
+    let mut chosen = Vec::new();
+    // Positions and sums below `need` already explored.
+    let mut seen = HashSet::new();
+    let mut stack = vec![Step::Visit(0, 0)];
+    while let Some(step) = stack.pop() {
+        match step {
+            Step::Visit(_, sum) if sum >= need => {
+                if best.as_ref().is_none_or(|&(best, _)| sum < best) {
+                    best = Some((sum, chosen.clone()));
+                    if sum == need {
+                        break;
+                    }
+                }
+            }
+            Step::Visit(pos, sum) => {
+                if sum + rest[pos] < need || !seen.insert((pos, sum)) {
+                    continue;
+                }
+                stack.push(Step::Visit(pos + 1, sum));
+                stack.push(Step::Untake);
+                stack.push(Step::Take(pos, sum));
+            }
+            Step::Take(pos, sum) => {
+                chosen.push(pos);
+                stack.push(Step::Visit(ends[pos], sum + sizes[pos]));
+            }
+            Step::Untake => {
+                chosen.pop();
+            }
+        }
+    }
+    let (_, chosen) = best?;
+    Some(chosen.into_iter().map(|i| dirs[i].0).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Directories `/a`, `/a/x`, `/b`, `/c` with sizes 10, 4, 7 and 5 (`/a` also holds 6 directly).
+    fn example() -> FileSystem {
+        let mut fs = FileSystem::new();
+        let a = fs.add_dir(FileSystem::ROOT, "a").unwrap();
+        fs.add_file(a, "f", 6).unwrap();
+        let x = fs.add_dir(a, "x").unwrap();
+        fs.add_file(x, "f", 4).unwrap();
+        let b = fs.add_dir(FileSystem::ROOT, "b").unwrap();
+        fs.add_file(b, "f", 7).unwrap();
+        let c = fs.add_dir(FileSystem::ROOT, "c").unwrap();
+        fs.add_file(c, "f", 5).unwrap();
+        fs
+    }
+
+    fn paths(fs: &FileSystem, need: usize) -> Option<Vec<String>> {
+        plan(fs, need).map(|ids| ids.iter().map(|&id| fs.path(id)).collect())
+    }
+
+    #[test]
+    fn example_plans() {
+        let fs = example();
+        assert_eq!(paths(&fs, 0), Some(vec![]));
+        assert_eq!(paths(&fs, 4), Some(vec!["/a/x".to_string()]));
+        // 4 + 5 beats both 10 and 7 + 5.
+        assert_eq!(paths(&fs, 9).unwrap().len(), 2);
+        assert_eq!(
+            plan(&fs, 9)
+                .unwrap()
+                .iter()
+                .map(|&id| fs.size(id))
+                .sum::<usize>(),
+            9
+        );
+        // 4 + 7 = 11 is the best way to free 11; /a with /a/x would be nested.
+        assert_eq!(
+            paths(&fs, 11),
+            Some(vec!["/a/x".to_string(), "/b".to_string()])
+        );
+        assert_eq!(paths(&fs, 22).unwrap().len(), 3);
+        assert_eq!(paths(&fs, 23), None);
+    }
+
+    #[test]
+    fn equal_siblings_without_exact_plan() {
+        let mut fs = FileSystem::new();
+        for i in 0..200 {
+            let dir = fs.add_dir(FileSystem::ROOT, &format!("d{:03}", i)).unwrap();
+            fs.add_file(dir, "f", 2).unwrap();
+        }
+        let removed = plan(&fs, 61).unwrap();
+        assert_eq!(removed.len(), 31);
+        assert!(removed.iter().all(|&id| fs.size(id) == 2));
+        assert_eq!(plan(&fs, 399).unwrap().len(), 200);
+        assert_eq!(plan(&fs, 401), None);
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let fs = example();
+        let dirs = fs.iter().skip(1).filter(|&id| fs.is_dir(id)).collect_vec();
+        let nested = |a: NodeId, b: NodeId| fs.path(b).starts_with(&format!("{}/", fs.path(a)));
+        for need in 0..=23 {
+            let best = dirs
+                .iter()
+                .copied()
+                .powerset()
+                .filter(|set| {
+                    set.iter()
+                        .tuple_combinations()
+                        .all(|(&a, &b)| !nested(a, b) && !nested(b, a))
+                })
+                .map(|set| set.iter().map(|&id| fs.size(id)).sum::<usize>())
+                .filter(|&s| s >= need)
+                .min();
+            let planned = plan(&fs, need).map(|ids| ids.iter().map(|&id| fs.size(id)).sum());
+            assert_eq!(planned, best, "need {}", need);
+        }
+    }
+}
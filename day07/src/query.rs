@@ -0,0 +1,323 @@
+use std::str::FromStr;
+
+use crate::fs::{FileSystem, NodeId};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Field {
+    Size,
+    Depth,
+    Name,
+    Path,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    /// Glob match with `*` and `?`.
+    Glob,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Value {
+    Number(usize),
+    Text(String),
+}
+
+/// Predicate over directories, e.g. `size <= 100000 and not name ~ "a*"`.
+///
+/// Fields are `size`, `depth` (the root has depth 0), `name` and `path`. Numbers compare
+/// with `<`, `<=`, `>`, `>=`, `==` and `!=`, text with `==`, `!=` and `~` (glob).
+/// Predicates combine with `and`, `or`, `not` and parentheses.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Query {
+    Compare(Field, Op, Value),
+    Not(Box<Query>),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+}
+
+impl Query {
+    /// Whether the node `id`, lying `depth` levels below the root, matches the query.
+    pub fn matches(&self, fs: &FileSystem, id: NodeId, depth: usize) -> bool {
+        match self {
+            Query::Compare(field, op, value) => match (field, value) {
+                (Field::Size, &Value::Number(x)) => compare(fs.size(id), *op, x),
+                (Field::Depth, &Value::Number(x)) => compare(depth, *op, x),
+                (Field::Name, Value::Text(s)) => compare_text(&fs.node(id).name, *op, s),
+                (Field::Path, Value::Text(s)) => compare_text(&fs.path(id), *op, s),
+                _ => unreachable!("type-checked when parsing"),
+            },
+            Query::Not(q) => !q.matches(fs, id, depth),
+            Query::And(a, b) => a.matches(fs, id, depth) && b.matches(fs, id, depth),
+            Query::Or(a, b) => a.matches(fs, id, depth) || b.matches(fs, id, depth),
+        }
+    }
+}
+
+fn compare(a: usize, op: Op, b: usize) -> bool {
+    match op {
+        Op::Lt => a < b,
+        Op::Le => a <= b,
+        Op::Gt => a > b,
+        Op::Ge => a >= b,
+        Op::Eq => a == b,
+        Op::Ne => a != b,
+        Op::Glob => unreachable!("type-checked when parsing"),
+    }
+}
+
+fn compare_text(a: &str, op: Op, b: &str) -> bool {
+    match op {
+        Op::Eq => a == b,
+        Op::Ne => a != b,
+        Op::Glob => glob_match(b, a),
+        _ => unreachable!("type-checked when parsing"),
+    }
+}
+
+/// Matches `text` against `pattern`, where `*` matches any sequence and `?` any single char.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` in the pattern, and where in the text it started matching.
+    let mut star = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((sp, st)) = star {
+            p = sp + 1;
+            t = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Token {
+    Word(String),
+    Text(String),
+    Op(Op),
+    Open,
+    Close,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let chars = s.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match (c, next) {
+            _ if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('<', Some('=')) => (Token::Op(Op::Le), 2),
+            ('>', Some('=')) => (Token::Op(Op::Ge), 2),
+            ('=', Some('=')) => (Token::Op(Op::Eq), 2),
+            ('!', Some('=')) => (Token::Op(Op::Ne), 2),
+            ('<', _) => (Token::Op(Op::Lt), 1),
+            ('>', _) => (Token::Op(Op::Gt), 1),
+            ('=', _) => (Token::Op(Op::Eq), 1),
+            ('~', _) => (Token::Op(Op::Glob), 1),
+            ('"', _) => {
+                let end = (i + 1..chars.len())
+                    .find(|&j| chars[j] == '"')
+                    .ok_or_else(|| format!("Unclosed string at column {}", i + 1))?;
+                (Token::Text(chars[i + 1..end].iter().collect()), end + 1 - i)
+            }
+            _ => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|&&c| !c.is_whitespace() && !"()<>=!~\"".contains(c))
+                    .count();
+                if len == 0 {
+                    return Err(format!("Unexpected {:?} at column {}", c, i + 1));
+                }
+                (Token::Word(chars[i..i + len].iter().collect()), len)
+            }
+        };
+        tokens.push(token);
+        i += len;
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser over the tokens:
+///
+/// ```text
+/// or      := and ("or" and)*
+/// and     := unary ("and" unary)*
+/// unary   := "not" unary | "(" or ")" | field op value
+/// ```
+struct QueryParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl QueryParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or("Unexpected end of query")?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Word(w)) if w == keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Query, String> {
+        let mut query = self.and()?;
+        while self.eat_keyword("or") {
+            query = Query::Or(Box::new(query), Box::new(self.and()?));
+        }
+        Ok(query)
+    }
+
+    fn and(&mut self) -> Result<Query, String> {
+        let mut query = self.unary()?;
+        while self.eat_keyword("and") {
+            query = Query::And(Box::new(query), Box::new(self.unary()?));
+        }
+        Ok(query)
+    }
+
+    fn unary(&mut self) -> Result<Query, String> {
+        if self.eat_keyword("not") {
+            return Ok(Query::Not(Box::new(self.unary()?)));
+        }
+        match self.next()? {
+            Token::Open => {
+                let query = self.or()?;
+                match self.next()? {
+                    Token::Close => Ok(query),
+                    token => Err(format!("Expected ')', found {:?}", token)),
+                }
+            }
+            Token::Word(field) => self.comparison(&field),
+            token => Err(format!("Expected a field, found {:?}", token)),
+        }
+    }
+
+    fn comparison(&mut self, field: &str) -> Result<Query, String> {
+        let field = match field {
+            "size" => Field::Size,
+            "depth" => Field::Depth,
+            "name" => Field::Name,
+            "path" => Field::Path,
+            _ => return Err(format!("Unknown field {:?}", field)),
+        };
+        let op = match self.next()? {
+            Token::Op(op) => op,
+            token => return Err(format!("Expected an operator, found {:?}", token)),
+        };
+        let value = match self.next()? {
+            Token::Word(w) | Token::Text(w) => w,
+            token => return Err(format!("Expected a value, found {:?}", token)),
+        };
+        let value = match field {
+            Field::Size | Field::Depth => {
+                if op == Op::Glob {
+                    return Err(format!("Cannot use '~' on {:?}", field));
+                }
+                Value::Number(
+                    value
+                        .parse()
+                        .map_err(|e| format!("Bad number {:?}: {}", value, e))?,
+                )
+            }
+            Field::Name | Field::Path => {
+                if !matches!(op, Op::Eq | Op::Ne | Op::Glob) {
+                    return Err(format!("Cannot compare {:?} with {:?}", field, op));
+                }
+                Value::Text(value)
+            }
+        };
+        Ok(Query::Compare(field, op, value))
+    }
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = QueryParser {
+            tokens: tokenize(s)?,
+            pos: 0,
+        };
+        let query = parser.or()?;
+        match parser.peek() {
+            None => Ok(query),
+            Some(token) => Err(format!("Unexpected {:?} after the query", token)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("a*", "abc"));
+        assert!(glob_match("*.txt", "b.txt"));
+        assert!(glob_match("?", "e"));
+        assert!(glob_match("*a*b*", "xxaxxbxx"));
+        assert!(!glob_match("a*", "ba"));
+        assert!(!glob_match("?", ""));
+        assert!(!glob_match("*.txt", "b.txt.gz"));
+    }
+
+    #[test]
+    fn parsing() {
+        let q = "size <= 100000 and not name ~ \"a*\" or depth==1"
+            .parse::<Query>()
+            .unwrap();
+        let expected = Query::Or(
+            Box::new(Query::And(
+                Box::new(Query::Compare(Field::Size, Op::Le, Value::Number(100000))),
+                Box::new(Query::Not(Box::new(Query::Compare(
+                    Field::Name,
+                    Op::Glob,
+                    Value::Text("a*".to_string()),
+                )))),
+            )),
+            Box::new(Query::Compare(Field::Depth, Op::Eq, Value::Number(1))),
+        );
+        assert_eq!(q, expected);
+        assert!("size ~ 10".parse::<Query>().is_err());
+        assert!("name < a".parse::<Query>().is_err());
+        assert!("(size > 1".parse::<Query>().is_err());
+        assert!("size > 1 2".parse::<Query>().is_err());
+        assert!("color == red".parse::<Query>().is_err());
+    }
+}
@@ -0,0 +1,101 @@
+use itertools::Itertools;
+
+use crate::fs::{Entry, FileSystem, NodeId};
+
+/// Renders the tree under `start` in the puzzle's format, one node per line,
+/// not descending deeper than `max_depth` levels:
+///
+/// ```text
+/// - / (dir, size=48381165)
+///   - a (dir, size=94853)
+///     - e (dir, size=584)
+/// ```
+pub fn render_tree(fs: &FileSystem, start: NodeId, max_depth: usize) -> Vec<String> {
+    fs.walk(start, max_depth)
+        .map(|(id, depth)| {
+            let node = fs.node(id);
+            let kind = match node.entry {
+                Entry::Dir { .. } => "dir",
+                Entry::File { .. } => "file",
+            };
+            format!(
+                "{}- {} ({}, size={})",
+                "  ".repeat(depth),
+                node.name,
+                kind,
+                fs.size(id)
+            )
+        })
+        .collect()
+}
+
+/// Directories under `start` (inclusive), largest first, ties broken by path order.
+pub fn du(fs: &FileSystem, start: NodeId) -> Vec<NodeId> {
+    fs.walk(start, usize::MAX)
+        .map(|(id, _)| id)
+        .filter(|&id| fs.is_dir(id))
+        .sorted_by_key(|&id| std::cmp::Reverse(fs.size(id)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    const SAMPLE: &str = "\
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+    #[test]
+    fn sample_tree() {
+        let lines = SAMPLE.lines().map(|s| s.to_string()).collect_vec();
+        let fs = parse(&lines).unwrap().fs;
+        let expected = "\
+- / (dir, size=48381165)
+  - a (dir, size=94853)
+    - e (dir, size=584)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir, size=24933642)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)";
+        assert_eq!(
+            render_tree(&fs, FileSystem::ROOT, usize::MAX).join("\n"),
+            expected
+        );
+        assert_eq!(render_tree(&fs, FileSystem::ROOT, 1).len(), 5);
+        let sizes = du(&fs, FileSystem::ROOT)
+            .iter()
+            .map(|&id| fs.size(id))
+            .collect_vec();
+        assert_eq!(sizes, [48381165, 24933642, 94853, 584]);
+    }
+}
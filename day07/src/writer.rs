@@ -0,0 +1,88 @@
+use clap::ValueEnum;
+use itertools::Itertools;
+
+use crate::fs::{Entry, FileSystem, NodeId};
+
+/// Order of the entries within each `ls` listing (and of the `cd`s into subdirectories).
+#[derive(ValueEnum, Copy, Clone, Debug)]
+pub enum Order {
+    Name,
+    /// Largest first.
+    Size,
+    DirsFirst,
+}
+
+fn sorted_children(fs: &FileSystem, id: NodeId, order: Order) -> Vec<NodeId> {
+    let children = fs.children(id);
+    match order {
+        Order::Name => children.collect(),
+        Order::Size => children
+            .sorted_by_key(|&c| std::cmp::Reverse(fs.size(c)))
+            .collect(),
+        Order::DirsFirst => children.sorted_by_key(|&c| !fs.is_dir(c)).collect(),
+    }
+}
+
+/// Writes a terminal transcript that reconstructs `fs` when replayed, listing every
+/// directory once. Empty directories are listed by their parent but not entered.
+pub fn write_transcript(fs: &FileSystem, order: Order) -> Vec<String> {
+    enum Step {
+        Enter(NodeId),
+        Leave,
+    }
+
+    let mut lines = vec!["$ cd /".to_string()];
+    let mut stack = vec![Step::Enter(FileSystem::ROOT)];
+    while let Some(step) = stack.pop() {
+        let id = match step {
+            Step::Enter(id) => id,
+            Step::Leave => {
+                lines.push("$ cd ..".to_string());
+                continue;
+            }
+        };
+        if id != FileSystem::ROOT {
+            lines.push(format!("$ cd {}", fs.node(id).name));
+        }
+        lines.push("$ ls".to_string());
+        let children = sorted_children(fs, id, order);
+        for &child in &children {
+            let node = fs.node(child);
+            match node.entry {
+                Entry::Dir { .. } => lines.push(format!("dir {}", node.name)),
+                Entry::File { size } => lines.push(format!("{} {}", size, node.name)),
+            }
+        }
+        for &child in children.iter().rev() {
+            if fs.children(child).next().is_some() {
+                stack.push(Step::Leave);
+                stack.push(Step::Enter(child));
+            }
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use crate::report::render_tree;
+
+    #[test]
+    fn round_trip() {
+        let mut fs = FileSystem::new();
+        let a = fs.add_dir(FileSystem::ROOT, "a").unwrap();
+        let e = fs.add_dir(a, "e").unwrap();
+        fs.add_file(e, "i", 584).unwrap();
+        fs.add_file(a, "f", 29116).unwrap();
+        fs.add_file(FileSystem::ROOT, "b.txt", 14848514).unwrap();
+        fs.add_dir(FileSystem::ROOT, "empty").unwrap();
+        let tree = render_tree(&fs, FileSystem::ROOT, usize::MAX);
+        for order in [Order::Name, Order::Size, Order::DirsFirst] {
+            let t = parse(&write_transcript(&fs, order)).unwrap();
+            assert!(t.warnings.is_empty());
+            assert_eq!(render_tree(&t.fs, FileSystem::ROOT, usize::MAX), tree);
+        }
+    }
+}
@@ -1,9 +1,9 @@
-use std::cmp::max;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 
 use clap::Parser;
+use common::interval::{Interval, IntervalSet};
 use itertools::Itertools;
 use lazy_regex::regex_captures;
 
@@ -103,7 +103,7 @@ fn main() -> color_eyre::Result<()> {
 
     let data = BufReader::new(File::open(args.input)?)
         .lines()
-        .filter_map(|x| x.ok())
+        .map_while(Result::ok)
         .map(|line| {
             let (_, xs, ys, xb, yb) = regex_captures!(
                 r"Sensor at x=(-?\d+), y=(-?\d+): closest beacon is at x=(-?\d+), y=(-?\d+)",
@@ -131,29 +131,17 @@ fn main() -> color_eyre::Result<()> {
         .map(|sensor| sensor.pos.0 + sensor.radius as i32)
         .max()
         .unwrap();
-    let intervals = data
+    let covered: IntervalSet<i32> = data
         .iter()
         .filter_map(|sensor| {
             let dist_row = sensor.pos.1.abs_diff(args.row);
             (sensor.radius >= dist_row).then(|| {
                 let d = (sensor.radius - dist_row) as i32;
-                (sensor.pos.0 - d, sensor.pos.0 + d)
+                Interval::new(sensor.pos.0 - d, sensor.pos.0 + d)
             })
         })
-        .collect_vec();
-    let count_union: usize = intervals
-        .iter()
-        .copied()
-        .sorted_unstable_by_key(|interval| interval.0)
-        .coalesce(|prev, next| {
-            if next.0 <= prev.1 {
-                Ok((prev.0, max(prev.1, next.1)))
-            } else {
-                Err((prev, next))
-            }
-        })
-        .map(|(x, y)| (y - x + 1) as usize)
-        .sum();
+        .collect();
+    let count_union = covered.len() as usize;
     let count_beacons: usize = data
         .iter()
         .map(|sensor| sensor.beacon)